@@ -21,6 +21,71 @@ pub trait ToBytes {
     W: Write;
 }
 
+/// A `Read + Seek` adapter that clamps reads to `limit` bytes from the
+/// position it was created at, while still allowing seeks within that window.
+/// Mirrors decomp-toolkit's `TakeSeek`, and is used to keep a malformed
+/// `next_block` pointer from spilling a block read into an adjacent superblock:
+/// once the window is exhausted reads return EOF, so `read_exact` surfaces a
+/// localized `UnexpectedEof` instead of silently crossing a region boundary.
+pub struct TakeSeek<R> {
+  inner: R,
+  start: u64,
+  limit: u64,
+  pos: u64,
+}
+
+impl<R> Read for TakeSeek<R>
+where
+  R: Read,
+{
+  fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+    let remaining = self.limit.saturating_sub(self.pos);
+    let max = remaining.min(buf.len() as u64) as usize;
+    let read = self.inner.read(&mut buf[..max])?;
+    self.pos += read as u64;
+    Ok(read)
+  }
+}
+
+impl<R> Seek for TakeSeek<R>
+where
+  R: Seek,
+{
+  fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+    let new_pos = match pos {
+      SeekFrom::Start(n) => n,
+      SeekFrom::Current(n) => self.pos.saturating_add_signed(n),
+      SeekFrom::End(n) => self.limit.saturating_add_signed(n),
+    };
+    self.inner.seek(SeekFrom::Start(self.start + new_pos))?;
+    self.pos = new_pos;
+    Ok(new_pos)
+  }
+}
+
+/// Extension trait providing [`TakeSeek`] over any `Read + Seek`, capturing the
+/// current stream position as the window start.
+pub trait TakeSeekExt: Read + Seek {
+  fn take_seek(self, limit: u64) -> TakeSeek<Self>
+  where
+    Self: Sized;
+}
+
+impl<R> TakeSeekExt for R
+where
+  R: Read + Seek,
+{
+  fn take_seek(mut self, limit: u64) -> TakeSeek<Self> {
+    let start = self.stream_position().unwrap_or(0);
+    TakeSeek {
+      inner: self,
+      start,
+      limit,
+      pos: 0,
+    }
+  }
+}
+
 pub trait BlockAlign {
   const HEADER_SIZE: u64;
   const SIZE: u64;
@@ -45,6 +110,116 @@ pub trait BlockAlign {
   fn initial_header() -> Vec<u8>;
 }
 
+/// A per-file data codec. The tag is stored in each `FileHeader` so `read`
+/// knows how to reassemble the chain. Codecs other than `Store` are gated
+/// behind their own cargo feature so the dependency stays optional, matching
+/// the per-chunk codec selection in nod-rs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Codec {
+  /// No compression — the data blocks hold the payload verbatim.
+  #[default]
+  Store,
+  #[cfg(feature = "zstd")]
+  Zstd,
+  #[cfg(feature = "lzma")]
+  Lzma,
+  #[cfg(feature = "bzip2")]
+  Bzip2,
+}
+
+impl Codec {
+  /// The one-byte tag persisted in the header.
+  fn tag(self) -> u8 {
+    match self {
+      Codec::Store => 0,
+      #[cfg(feature = "zstd")]
+      Codec::Zstd => 1,
+      #[cfg(feature = "lzma")]
+      Codec::Lzma => 2,
+      #[cfg(feature = "bzip2")]
+      Codec::Bzip2 => 3,
+    }
+  }
+
+  /// Resolves a persisted tag back to a codec, erroring if the image was
+  /// written with a codec this build wasn't compiled with.
+  fn from_tag(tag: u8) -> io::Result<Self> {
+    Ok(match tag {
+      0 => Codec::Store,
+      #[cfg(feature = "zstd")]
+      1 => Codec::Zstd,
+      #[cfg(feature = "lzma")]
+      2 => Codec::Lzma,
+      #[cfg(feature = "bzip2")]
+      3 => Codec::Bzip2,
+      other => {
+        return Err(io::Error::new(
+          io::ErrorKind::InvalidData,
+          format!("unknown or unsupported codec tag: {other}"),
+        ))
+      }
+    })
+  }
+
+  fn compress(self, data: &[u8]) -> io::Result<Vec<u8>> {
+    match self {
+      Codec::Store => Ok(data.to_vec()),
+      #[cfg(feature = "zstd")]
+      Codec::Zstd => zstd::stream::encode_all(data, 0),
+      #[cfg(feature = "lzma")]
+      Codec::Lzma => {
+        let mut out = vec![];
+        lzma_rs::xz_compress(&mut io::Cursor::new(data), &mut out)?;
+        Ok(out)
+      }
+      #[cfg(feature = "bzip2")]
+      Codec::Bzip2 => {
+        use std::io::Read as _;
+        let mut out = vec![];
+        bzip2::read::BzEncoder::new(data, bzip2::Compression::default())
+          .read_to_end(&mut out)?;
+        Ok(out)
+      }
+    }
+  }
+
+  fn decompress(self, data: &[u8]) -> io::Result<Vec<u8>> {
+    match self {
+      Codec::Store => Ok(data.to_vec()),
+      #[cfg(feature = "zstd")]
+      Codec::Zstd => zstd::stream::decode_all(data),
+      #[cfg(feature = "lzma")]
+      Codec::Lzma => {
+        let mut out = vec![];
+        lzma_rs::xz_decompress(&mut io::Cursor::new(data), &mut out)
+          .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(out)
+      }
+      #[cfg(feature = "bzip2")]
+      Codec::Bzip2 => {
+        use std::io::Read as _;
+        let mut out = vec![];
+        bzip2::read::BzDecoder::new(data).read_to_end(&mut out)?;
+        Ok(out)
+      }
+    }
+  }
+}
+
+/// Tunables for a [`Filesystem`], passed to [`Filesystem::new`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FilesystemOptions {
+  /// The codec applied to file payloads on `insert`.
+  pub codec: Codec,
+}
+
+/// The little-endian magic constant (`"RFS\x01"`) stamped at the start of a
+/// main superblock so a corrupt or foreign file is rejected on open.
+pub const MAGIC: u32 = 0x0153_4652;
+
+/// The on-disk format version. Bumped when the layout changes incompatibly.
+pub const VERSION: u32 = 1;
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub struct BlockKindMain {
@@ -58,7 +233,8 @@ pub struct BlockKindMain {
 }
 
 impl BlockAlign for BlockKindMain {
-  const HEADER_SIZE: u64 = 32;
+  // magic (4) + version (4) + four u64 pointers (32)
+  const HEADER_SIZE: u64 = 40;
   const SIZE: u64 = 0;
   const COUNT: u64 = 0;
 
@@ -72,6 +248,8 @@ impl ToBytes for BlockKindMain {
   where
     T: Write,
   {
+    writer.write_all(&MAGIC.to_le_bytes())?;
+    writer.write_all(&VERSION.to_le_bytes())?;
     writer.write_all(&self.free_header_ptr.to_le_bytes())?;
     writer.write_all(&self.free_title_ptr.to_le_bytes())?;
     writer.write_all(&self.free_data_ptr.to_le_bytes())?;
@@ -86,16 +264,36 @@ impl FromBytes for BlockKindMain {
   where
     T: Read,
   {
+    let mut magic = [0; 4];
+    let mut version = [0; 4];
     let mut free_header_ptr = [0; 8];
     let mut free_title_ptr = [0; 8];
     let mut free_data_ptr = [0; 8];
     let mut first_header_ptr = [0; 8];
 
+    reader.read_exact(&mut magic)?;
+    reader.read_exact(&mut version)?;
     reader.read_exact(&mut free_header_ptr)?;
     reader.read_exact(&mut free_title_ptr)?;
     reader.read_exact(&mut free_data_ptr)?;
     reader.read_exact(&mut first_header_ptr)?;
 
+    let magic = u32::from_le_bytes(magic);
+    if magic != MAGIC {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        FilesystemError::BadMagic(magic),
+      ));
+    }
+
+    let version = u32::from_le_bytes(version);
+    if version != VERSION {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        FilesystemError::UnsupportedVersion(version),
+      ));
+    }
+
     Ok(Self {
       free_header_ptr: u64::from_le_bytes(free_header_ptr),
       free_title_ptr: u64::from_le_bytes(free_title_ptr),
@@ -110,7 +308,8 @@ impl FromBytes for BlockKindMain {
 pub struct BlockKindHeader;
 impl BlockAlign for BlockKindHeader {
   const HEADER_SIZE: u64 = 16;
-  const SIZE: u64 = 32;
+  // prev_block (8) + next_block (8) + FileHeader (4 x u64 + u64 + u8 = 41)
+  const SIZE: u64 = 57;
   const COUNT: u64 = 128;
 
   fn initial_header() -> Vec<u8> {
@@ -160,7 +359,7 @@ where
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub struct Block<T>
 where
-  T: ?Sized + ToBytes + FromBytes,
+  T: ToBytes + FromBytes,
 {
   prev_block: u64,
   next_block: u64,
@@ -169,7 +368,7 @@ where
 
 impl<T> ToBytes for Block<T>
 where
-  T: ?Sized + ToBytes + FromBytes,
+  T: ToBytes + FromBytes,
 {
   fn to_bytes<W>(&self, writer: &mut W) -> Result<(), io::Error>
   where
@@ -184,7 +383,7 @@ where
 
 impl<T> FromBytes for Block<T>
 where
-  T: ?Sized + ToBytes + FromBytes,
+  T: ToBytes + FromBytes,
 {
   fn from_bytes<R>(reader: &mut R) -> Result<Self, io::Error>
   where
@@ -211,6 +410,20 @@ where
 pub struct FileHeader {
   start_title_block: u64,
   start_data_block: u64,
+
+  /// The number of valid title bytes across the title-block chain.
+  title_len: u64,
+
+  /// The number of stored (possibly compressed) data bytes across the
+  /// data-block chain.
+  data_len: u64,
+
+  /// The uncompressed payload length; equals `data_len` when `codec` is
+  /// `Store`.
+  raw_len: u64,
+
+  /// The codec tag the payload was written with (see [`Codec::from_tag`]).
+  codec: u8,
 }
 
 impl ToBytes for FileHeader {
@@ -220,6 +433,10 @@ impl ToBytes for FileHeader {
   {
     writer.write_all(&self.start_title_block.to_le_bytes())?;
     writer.write_all(&self.start_data_block.to_le_bytes())?;
+    writer.write_all(&self.title_len.to_le_bytes())?;
+    writer.write_all(&self.data_len.to_le_bytes())?;
+    writer.write_all(&self.raw_len.to_le_bytes())?;
+    writer.write_all(&self.codec.to_le_bytes())?;
     Ok(())
   }
 }
@@ -231,13 +448,25 @@ impl FromBytes for FileHeader {
   {
     let mut start_title_block = [0; 8];
     let mut start_data_block = [0; 8];
+    let mut title_len = [0; 8];
+    let mut data_len = [0; 8];
+    let mut raw_len = [0; 8];
+    let mut codec = [0; 1];
 
     reader.read_exact(&mut start_title_block)?;
     reader.read_exact(&mut start_data_block)?;
+    reader.read_exact(&mut title_len)?;
+    reader.read_exact(&mut data_len)?;
+    reader.read_exact(&mut raw_len)?;
+    reader.read_exact(&mut codec)?;
 
     Ok(Self {
       start_title_block: u64::from_le_bytes(start_title_block),
       start_data_block: u64::from_le_bytes(start_data_block),
+      title_len: u64::from_le_bytes(title_len),
+      data_len: u64::from_le_bytes(data_len),
+      raw_len: u64::from_le_bytes(raw_len),
+      codec: u8::from_le_bytes(codec),
     })
   }
 }
@@ -315,12 +544,21 @@ pub enum InitializationError {
 pub enum GenericError {
   #[error(transparent)]
   IO(#[from] io::Error),
+
+  #[error("out of {0} blocks")]
+  OutOfSpace(&'static str),
 }
 
 #[derive(Debug, Error)]
 pub enum FilesystemError {
   #[error(transparent)]
   InitializationError(InitializationError),
+
+  #[error("bad magic number: expected {MAGIC:#010x}, found {0:#010x}")]
+  BadMagic(u32),
+
+  #[error("unsupported format version: {0} (this build supports {VERSION})")]
+  UnsupportedVersion(u32),
 }
 
 impl From<InitializationError> for FilesystemError {
@@ -334,14 +572,15 @@ where
   T: Read + Write + Seek,
 {
   inner: T,
+  options: FilesystemOptions,
 }
 
 impl<T> Filesystem<T>
 where
   T: Read + Write + Seek,
 {
-  pub fn new(inner: T) -> Self {
-    Filesystem { inner }
+  pub fn new(inner: T, options: FilesystemOptions) -> Self {
+    Filesystem { inner, options }
   }
 
   fn clear_and_check_size(
@@ -417,10 +656,7 @@ where
       let header_block = Block::<FileHeader> {
         prev_block,
         next_block,
-        data: FileHeader {
-          start_title_block: 0,
-          start_data_block: 0,
-        },
+        data: FileHeader::default(),
       };
 
       prev_block = cursor;
@@ -451,7 +687,7 @@ where
 
       prev_block = cursor;
 
-      title_block.to_bytes(&mut self.inner);
+      title_block.to_bytes(&mut self.inner)?;
     }
 
     // Initialize Data Superblock
@@ -477,7 +713,7 @@ where
 
       prev_block = cursor;
 
-      data_block.to_bytes(&mut self.inner);
+      data_block.to_bytes(&mut self.inner)?;
     }
 
     Ok(())
@@ -507,7 +743,7 @@ where
     header_block: Block<FileHeader>,
   ) -> Result<(), GenericError> {
     self.inner.seek(SeekFrom::Start(index)).unwrap();
-    header_block.to_bytes(&mut self.inner);
+    header_block.to_bytes(&mut self.inner)?;
 
     Ok(())
   }
@@ -518,7 +754,7 @@ where
     title_block: Block<FileTitle>,
   ) -> Result<(), GenericError> {
     self.inner.seek(SeekFrom::Start(index)).unwrap();
-    title_block.to_bytes(&mut self.inner);
+    title_block.to_bytes(&mut self.inner)?;
 
     Ok(())
   }
@@ -549,7 +785,8 @@ where
       return Ok(None);
     }
     self.inner.seek(SeekFrom::Start(index)).unwrap();
-    let header_block: Block<FileHeader> = Block::from_bytes(&mut self.inner)?;
+    let mut limited = (&mut self.inner).take_seek(BlockKindHeader::block_size());
+    let header_block: Block<FileHeader> = Block::from_bytes(&mut limited)?;
     Ok(Some(header_block))
   }
 
@@ -561,7 +798,8 @@ where
       return Ok(None);
     }
     self.inner.seek(SeekFrom::Start(index)).unwrap();
-    let title_block: Block<FileTitle> = Block::from_bytes(&mut self.inner)?;
+    let mut limited = (&mut self.inner).take_seek(BlockKindTitle::block_size());
+    let title_block: Block<FileTitle> = Block::from_bytes(&mut limited)?;
     Ok(Some(title_block))
   }
 
@@ -573,85 +811,353 @@ where
       return Ok(None);
     }
     self.inner.seek(SeekFrom::Start(index)).unwrap();
-    let data_block: Block<FileData> = Block::from_bytes(&mut self.inner)?;
+    let mut limited = (&mut self.inner).take_seek(BlockKindData::block_size());
+    let data_block: Block<FileData> = Block::from_bytes(&mut limited)?;
     Ok(Some(data_block))
   }
 
+  /// Reads the name of a file by walking its title-block chain starting at
+  /// `start_title_block` for `title_len` bytes.
+  fn read_title(
+    &mut self,
+    start_title_block: u64,
+    title_len: u64,
+  ) -> Result<String, GenericError> {
+    let mut bytes: Vec<u8> = vec![];
+    let mut ptr = start_title_block;
+    while let Some(block) = self.read_title_block(ptr)? {
+      bytes.extend_from_slice(&block.data.data);
+      ptr = block.next_block;
+    }
+
+    bytes.truncate(title_len as usize);
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+  }
+
+  /// Lists the names of every valid file by walking the header chain starting
+  /// at `first_header_ptr`.
+  pub fn list(&mut self) -> Result<Vec<String>, GenericError> {
+    let main_header = self.read_main_header()?;
+
+    let mut names = vec![];
+    let mut ptr = main_header.first_header_ptr;
+    while let Some(header) = self.read_header_block(ptr)? {
+      names.push(
+        self.read_title(header.data.start_title_block, header.data.title_len)?,
+      );
+      ptr = header.next_block;
+    }
+
+    Ok(names)
+  }
+
+  /// Pops enough consecutive blocks off the free title list (starting at
+  /// `free_ptr`) to hold `bytes` in 16-byte chunks, links them via
+  /// `next_block` (terminating with `0`), and returns the chain's start block
+  /// plus the first free block not consumed.
+  fn write_title_chain(
+    &mut self,
+    free_ptr: u64,
+    bytes: &[u8],
+  ) -> Result<(u64, u64), GenericError> {
+    let chunks: Vec<&[u8]> = if bytes.is_empty() {
+      vec![&[][..]]
+    } else {
+      bytes.chunks(16).collect()
+    };
+
+    let mut addrs = vec![];
+    let mut ptr = free_ptr;
+    for _ in 0..chunks.len() {
+      let block = self
+        .read_title_block(ptr)?
+        .ok_or(GenericError::OutOfSpace("title"))?;
+      addrs.push(ptr);
+      ptr = block.next_block;
+    }
+    let next_free = ptr;
+
+    for (i, &addr) in addrs.iter().enumerate() {
+      let mut data = [0u8; 16];
+      data[..chunks[i].len()].copy_from_slice(chunks[i]);
+      let block = Block {
+        prev_block: if i == 0 { 0 } else { addrs[i - 1] },
+        next_block: addrs.get(i + 1).copied().unwrap_or(0),
+        data: FileTitle { data },
+      };
+      self.write_title_block(addr, block)?;
+    }
+
+    Ok((addrs[0], next_free))
+  }
+
+  /// Pops enough consecutive blocks off the free data list (starting at
+  /// `free_ptr`) to hold `bytes` in 112-byte chunks, links them via
+  /// `next_block` (terminating with `0`), and returns the chain's start block
+  /// plus the first free block not consumed.
+  fn write_data_chain(
+    &mut self,
+    free_ptr: u64,
+    bytes: &[u8],
+  ) -> Result<(u64, u64), GenericError> {
+    let chunks: Vec<&[u8]> = if bytes.is_empty() {
+      vec![&[][..]]
+    } else {
+      bytes.chunks(112).collect()
+    };
+
+    let mut addrs = vec![];
+    let mut ptr = free_ptr;
+    for _ in 0..chunks.len() {
+      let block = self
+        .read_data_block(ptr)?
+        .ok_or(GenericError::OutOfSpace("data"))?;
+      addrs.push(ptr);
+      ptr = block.next_block;
+    }
+    let next_free = ptr;
+
+    for (i, &addr) in addrs.iter().enumerate() {
+      let mut data = [0u8; 112];
+      data[..chunks[i].len()].copy_from_slice(chunks[i]);
+      let block = Block {
+        prev_block: if i == 0 { 0 } else { addrs[i - 1] },
+        next_block: addrs.get(i + 1).copied().unwrap_or(0),
+        data: FileData { data },
+      };
+      self.write_data_block(addr, block)?;
+    }
+
+    Ok((addrs[0], next_free))
+  }
+
   pub fn insert<D>(&mut self, name: String, data: D) -> Result<(), GenericError>
   where
     D: AsRef<[u8]>,
   {
-    let mut main_header = self.read_main_header().unwrap();
+    let mut main_header = self.read_main_header()?;
 
+    // Pop a header block off the free header list.
+    let free_header_ptr = main_header.free_header_ptr;
     let free_file_header = self
-      .read_header_block(main_header.free_header_ptr)?
-      .unwrap_or_else(|| todo!("no header block"));
+      .read_header_block(free_header_ptr)?
+      .ok_or(GenericError::OutOfSpace("header"))?;
+
+    // Span the name and payload across their respective block chains.
+    let name_bytes = name.as_bytes();
+    let (start_title_block, title_next_free) =
+      self.write_title_chain(main_header.free_title_ptr, name_bytes)?;
+
+    // Compress the payload, falling back to `Store` when it doesn't shrink.
+    let raw = data.as_ref();
+    let compressed = self.options.codec.compress(raw)?;
+    let (codec, stored) = if compressed.len() < raw.len() {
+      (self.options.codec, compressed)
+    } else {
+      (Codec::Store, raw.to_vec())
+    };
 
-    let prev_file_header =
-      self.read_header_block(free_file_header.prev_block).unwrap();
-    let next_file_header =
-      self.read_header_block(free_file_header.next_block).unwrap();
+    let (start_data_block, data_next_free) =
+      self.write_data_chain(main_header.free_data_ptr, &stored)?;
 
     let header_block = Block {
       prev_block: free_file_header.prev_block,
       next_block: main_header.first_header_ptr,
       data: FileHeader {
-        start_title_block: main_header.free_title_ptr,
-        start_data_block: main_header.free_data_ptr,
+        start_title_block,
+        start_data_block,
+        title_len: name_bytes.len() as u64,
+        data_len: stored.len() as u64,
+        raw_len: raw.len() as u64,
+        codec: codec.tag(),
       },
     };
+    self.write_header_block(free_header_ptr, header_block)?;
 
-    let free_title_block = self
-      .read_title_block(main_header.free_title_ptr)?
-      .unwrap_or_else(|| todo!("no title block"));
-    let free_data_block = self
-      .read_data_block(main_header.free_data_ptr)?
-      .unwrap_or_else(|| todo!("no data block"));
+    // Main Header: link the new header in and advance each free pointer past
+    // the blocks we consumed.
+    main_header.first_header_ptr = free_header_ptr;
+    main_header.free_header_ptr = free_file_header.next_block;
+    main_header.free_title_ptr = title_next_free;
+    main_header.free_data_ptr = data_next_free;
+    self.write_main_header(main_header)?;
 
-    let mut title_bytes: [u8; 16] = [0; 16];
-    if title_bytes.len() > 16 {
-      todo!("cannot store files with names greater than 16 bytes");
+    Ok(())
+  }
+
+  /// Pushes a whole title chain back onto the free title list by pointing its
+  /// tail at the current free head and returning the new free head.
+  fn free_title_chain(
+    &mut self,
+    start: u64,
+    free_head: u64,
+  ) -> Result<u64, GenericError> {
+    if start == 0 {
+      return Ok(free_head);
     }
-    for (i, byte) in name.as_bytes().iter().enumerate().take(112) {
-      title_bytes[i] = *byte;
+
+    let mut tail = start;
+    let mut ptr = start;
+    while let Some(block) = self.read_title_block(ptr)? {
+      tail = ptr;
+      ptr = block.next_block;
     }
 
-    let title_block = Block {
-      prev_block: 0,
-      next_block: 0,
-      data: FileTitle { data: title_bytes },
-    };
+    let mut tail_block = self.read_title_block(tail)?.unwrap();
+    tail_block.next_block = free_head;
+    self.write_title_block(tail, tail_block)?;
+
+    Ok(start)
+  }
 
-    let mut data_bytes: [u8; 112] = [0; 112];
-    if data_bytes.len() > 112 {
-      todo!("cannot store files with data greater than 112 bytes");
+  /// Pushes a whole data chain back onto the free data list by pointing its
+  /// tail at the current free head and returning the new free head.
+  fn free_data_chain(
+    &mut self,
+    start: u64,
+    free_head: u64,
+  ) -> Result<u64, GenericError> {
+    if start == 0 {
+      return Ok(free_head);
     }
-    for (i, byte) in data.as_ref().bytes().enumerate().take(112) {
-      data_bytes[i] = byte?;
+
+    let mut tail = start;
+    let mut ptr = start;
+    while let Some(block) = self.read_data_block(ptr)? {
+      tail = ptr;
+      ptr = block.next_block;
     }
 
-    let data_block = Block {
-      prev_block: 0,
-      next_block: 0,
-      data: FileData { data: data_bytes },
-    };
+    let mut tail_block = self.read_data_block(tail)?.unwrap();
+    tail_block.next_block = free_head;
+    self.write_data_block(tail, tail_block)?;
 
-    // Write Ops
-    self
-      .write_header_block(main_header.free_header_ptr, header_block)
-      .unwrap();
-    self
-      .write_title_block(main_header.free_title_ptr, title_block)
-      .unwrap();
-    self
-      .write_data_block(main_header.free_data_ptr, data_block)
-      .unwrap();
+    Ok(start)
+  }
 
-    // Main Header
-    main_header.first_header_ptr = main_header.free_header_ptr;
-    main_header.free_title_ptr = free_title_block.next_block;
-    self.write_main_header(main_header).unwrap();
+  /// Removes a file, returning `true` if it existed. The header is unlinked
+  /// from the valid-file chain and its header, title, and data blocks are
+  /// pushed back onto the respective free lists so they can be reused.
+  pub fn remove(&mut self, name: String) -> Result<bool, GenericError> {
+    let mut main_header = self.read_main_header()?;
+
+    // Walk the header chain, tracking the previous header so we can patch its
+    // next_block once we find the target.
+    let mut prev_addr = 0u64;
+    let mut addr = main_header.first_header_ptr;
+    while let Some(header) = self.read_header_block(addr)? {
+      let title =
+        self.read_title(header.data.start_title_block, header.data.title_len)?;
+      if title == name {
+        // Unlink from the valid-file chain.
+        if prev_addr == 0 {
+          main_header.first_header_ptr = header.next_block;
+        } else {
+          let mut prev = self.read_header_block(prev_addr)?.unwrap();
+          prev.next_block = header.next_block;
+          self.write_header_block(prev_addr, prev)?;
+        }
+
+        // Reclaim the title and data chains.
+        main_header.free_title_ptr = self.free_title_chain(
+          header.data.start_title_block,
+          main_header.free_title_ptr,
+        )?;
+        main_header.free_data_ptr = self.free_data_chain(
+          header.data.start_data_block,
+          main_header.free_data_ptr,
+        )?;
+
+        // Reclaim the header block itself.
+        let freed = Block {
+          prev_block: 0,
+          next_block: main_header.free_header_ptr,
+          data: header.data,
+        };
+        self.write_header_block(addr, freed)?;
+        main_header.free_header_ptr = addr;
+
+        self.write_main_header(main_header)?;
+        return Ok(true);
+      }
+
+      prev_addr = addr;
+      addr = header.next_block;
+    }
 
-    Ok(())
+    Ok(false)
+  }
+
+  /// Reads a file by walking the data-block chain of the header whose title
+  /// matches `name`, truncating to the stored `data_len`.
+  pub fn read(&mut self, name: String) -> Result<Vec<u8>, GenericError> {
+    let main_header = self.read_main_header()?;
+
+    let mut ptr = main_header.first_header_ptr;
+    while let Some(header) = self.read_header_block(ptr)? {
+      let title =
+        self.read_title(header.data.start_title_block, header.data.title_len)?;
+      if title == name {
+        let mut bytes = vec![];
+        let mut dptr = header.data.start_data_block;
+        while let Some(block) = self.read_data_block(dptr)? {
+          bytes.extend_from_slice(&block.data.data);
+          dptr = block.next_block;
+        }
+        bytes.truncate(header.data.data_len as usize);
+        let codec = Codec::from_tag(header.data.codec)?;
+        let mut raw = codec.decompress(&bytes)?;
+        raw.truncate(header.data.raw_len as usize);
+        return Ok(raw);
+      }
+      ptr = header.next_block;
+    }
+
+    Ok(vec![])
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::io::Cursor;
+
+  use super::*;
+
+  fn new_fs() -> Filesystem<Cursor<Vec<u8>>> {
+    let size = BlockKindMain::super_block_size()
+      + BlockKindHeader::super_block_size()
+      + BlockKindTitle::super_block_size()
+      + BlockKindData::super_block_size();
+    let mut fs = Filesystem::new(Cursor::new(vec![]), FilesystemOptions::default());
+    fs.init(size).unwrap();
+    fs
+  }
+
+  #[test]
+  fn test_remove_returns_false_for_missing() {
+    let mut fs = new_fs();
+    assert!(!fs.remove("nope.txt".to_owned()).unwrap());
+  }
+
+  #[test]
+  fn test_insert_remove_reinsert_reuses_blocks() {
+    let mut fs = new_fs();
+
+    fs.insert("a.txt".to_owned(), "hello").unwrap();
+    let after_insert = fs.read_main_header().unwrap();
+
+    assert!(fs.remove("a.txt".to_owned()).unwrap());
+    assert!(fs.list().unwrap().is_empty());
+
+    // Re-inserting a payload of the same shape should hand back the blocks we
+    // just freed rather than consuming fresh ones.
+    fs.insert("a.txt".to_owned(), "world").unwrap();
+    let after_reinsert = fs.read_main_header().unwrap();
+
+    assert_eq!(after_insert.free_header_ptr, after_reinsert.free_header_ptr);
+    assert_eq!(after_insert.free_title_ptr, after_reinsert.free_title_ptr);
+    assert_eq!(after_insert.free_data_ptr, after_reinsert.free_data_ptr);
+
+    assert_eq!(fs.read("a.txt".to_owned()).unwrap(), b"world");
   }
 }
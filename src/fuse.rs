@@ -0,0 +1,478 @@
+//! A FUSE server adapter that answers the kernel's low-level FUSE protocol
+//! messages on behalf of a [`Filesystem`]. [`Server::handle_message`] handles
+//! one already-decoded request and returns the framed reply; this module does
+//! not open `/dev/fuse` or perform the mount itself.
+//!
+//! Requests arrive as a [`fuse_in_header`]-shaped [`InHeader`] followed by an
+//! opcode-specific body; responses are always a [`fuse_out_header`]-shaped
+//! prefix followed by the opcode's reply body (for `READDIR`, NUL-separated
+//! names rather than `fuse_dirent` records). The kernel learns a file's
+//! `nodeid` from `LOOKUP`/`CREATE` and addresses it by that id in later
+//! `READ`/`WRITE`/`GETATTR` calls rather than by name, so the server keeps a
+//! nodeid-to-name table for the lifetime of the mount.
+
+use std::collections::HashMap;
+use std::io::{self, Read, Seek, Write};
+
+use crate::filesystem::Filesystem;
+
+/// The nodeid the kernel always uses for the mount's root directory.
+pub const ROOT_NODEID: u64 = 1;
+
+const S_IFREG: u32 = 0o100000;
+const S_IFDIR: u32 = 0o040000;
+
+/// The subset of FUSE low-level opcodes this adapter understands. The numeric
+/// values match `fuse_opcode` from the kernel ABI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum Opcode {
+  Lookup = 1,
+  GetAttr = 3,
+  Unlink = 10,
+  Open = 14,
+  Read = 15,
+  Write = 16,
+  ReadDir = 28,
+  Create = 35,
+}
+
+impl Opcode {
+  /// Maps a raw opcode off the wire onto one of the handlers we implement,
+  /// returning `None` for anything we don't support yet.
+  pub fn from_raw(raw: u32) -> Option<Self> {
+    Some(match raw {
+      1 => Self::Lookup,
+      3 => Self::GetAttr,
+      10 => Self::Unlink,
+      14 => Self::Open,
+      15 => Self::Read,
+      16 => Self::Write,
+      28 => Self::ReadDir,
+      35 => Self::Create,
+      _ => return None,
+    })
+  }
+}
+
+/// The fixed-size header in front of every FUSE request (`fuse_in_header`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InHeader {
+  pub len: u32,
+  pub opcode: u32,
+  pub unique: u64,
+  pub nodeid: u64,
+  pub uid: u32,
+  pub gid: u32,
+  pub pid: u32,
+}
+
+impl InHeader {
+  /// The on-wire size of `fuse_in_header`.
+  pub const SIZE: usize = 40;
+
+  pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+    if bytes.len() < Self::SIZE {
+      return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+    }
+
+    let mut r = bytes;
+    let len = read_u32(&mut r)?;
+    let opcode = read_u32(&mut r)?;
+    let unique = read_u64(&mut r)?;
+    let nodeid = read_u64(&mut r)?;
+    let uid = read_u32(&mut r)?;
+    let gid = read_u32(&mut r)?;
+    let pid = read_u32(&mut r)?;
+    let _padding = read_u32(&mut r)?;
+
+    Ok(Self {
+      len,
+      opcode,
+      unique,
+      nodeid,
+      uid,
+      gid,
+      pid,
+    })
+  }
+}
+
+/// The fixed-size header in front of every FUSE response (`fuse_out_header`).
+struct OutHeader {
+  len: u32,
+  error: i32,
+  unique: u64,
+}
+
+impl OutHeader {
+  fn into_bytes(self) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(16);
+    bytes.extend_from_slice(&self.len.to_le_bytes());
+    bytes.extend_from_slice(&self.error.to_le_bytes());
+    bytes.extend_from_slice(&self.unique.to_le_bytes());
+    bytes
+  }
+}
+
+/// The body of a `READ` request (`fuse_read_in`). Only `offset` and `size` are
+/// kept; `fh` is skipped and `flags`/`lock_owner` are not parsed (the length
+/// check still covers the full struct), since [`Filesystem`] has no
+/// open-file-handle concept of its own.
+struct ReadIn {
+  offset: u64,
+  size: u32,
+}
+
+impl ReadIn {
+  const SIZE: usize = 40;
+
+  fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+    if bytes.len() < Self::SIZE {
+      return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+    }
+
+    let mut r = bytes;
+    let _fh = read_u64(&mut r)?;
+    let offset = read_u64(&mut r)?;
+    let size = read_u32(&mut r)?;
+
+    Ok(Self { offset, size })
+  }
+}
+
+/// The body of a `WRITE` request (`fuse_write_in`), immediately followed on
+/// the wire by `size` bytes of data to write.
+struct WriteIn {
+  offset: u64,
+  size: u32,
+}
+
+impl WriteIn {
+  const SIZE: usize = 40;
+
+  fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+    if bytes.len() < Self::SIZE {
+      return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+    }
+
+    let mut r = bytes;
+    let _fh = read_u64(&mut r)?;
+    let offset = read_u64(&mut r)?;
+    let size = read_u32(&mut r)?;
+
+    Ok(Self { offset, size })
+  }
+}
+
+/// A `fuse_attr` struct. Timestamps and other fields we have no backing data
+/// for are left zeroed.
+#[derive(Default)]
+struct Attr {
+  ino: u64,
+  size: u64,
+  mode: u32,
+}
+
+impl Attr {
+  fn into_bytes(self) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(88);
+    bytes.extend_from_slice(&self.ino.to_le_bytes());
+    bytes.extend_from_slice(&self.size.to_le_bytes());
+    bytes.extend_from_slice(&0u64.to_le_bytes()); // blocks
+    bytes.extend_from_slice(&0u64.to_le_bytes()); // atime
+    bytes.extend_from_slice(&0u64.to_le_bytes()); // mtime
+    bytes.extend_from_slice(&0u64.to_le_bytes()); // ctime
+    bytes.extend_from_slice(&0u32.to_le_bytes()); // atimensec
+    bytes.extend_from_slice(&0u32.to_le_bytes()); // mtimensec
+    bytes.extend_from_slice(&0u32.to_le_bytes()); // ctimensec
+    bytes.extend_from_slice(&self.mode.to_le_bytes());
+    bytes.extend_from_slice(&1u32.to_le_bytes()); // nlink
+    bytes.extend_from_slice(&0u32.to_le_bytes()); // uid
+    bytes.extend_from_slice(&0u32.to_le_bytes()); // gid
+    bytes.extend_from_slice(&0u32.to_le_bytes()); // rdev
+    bytes.extend_from_slice(&512u32.to_le_bytes()); // blksize
+    bytes.extend_from_slice(&0u32.to_le_bytes()); // padding
+    bytes
+  }
+}
+
+/// Response body for `LOOKUP`/`CREATE` (`fuse_entry_out`).
+struct EntryOut {
+  nodeid: u64,
+  attr: Attr,
+}
+
+impl EntryOut {
+  fn into_bytes(self) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(64 + 88);
+    bytes.extend_from_slice(&self.nodeid.to_le_bytes());
+    bytes.extend_from_slice(&0u64.to_le_bytes()); // generation
+    bytes.extend_from_slice(&1u64.to_le_bytes()); // entry_valid
+    bytes.extend_from_slice(&1u64.to_le_bytes()); // attr_valid
+    bytes.extend_from_slice(&0u32.to_le_bytes()); // entry_valid_nsec
+    bytes.extend_from_slice(&0u32.to_le_bytes()); // attr_valid_nsec
+    bytes.extend_from_slice(&self.attr.into_bytes());
+    bytes
+  }
+}
+
+/// Response body for `GETATTR` (`fuse_attr_out`).
+struct AttrOut {
+  attr: Attr,
+}
+
+impl AttrOut {
+  fn into_bytes(self) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(16 + 88);
+    bytes.extend_from_slice(&1u64.to_le_bytes()); // attr_valid
+    bytes.extend_from_slice(&0u32.to_le_bytes()); // attr_valid_nsec
+    bytes.extend_from_slice(&0u32.to_le_bytes()); // dummy
+    bytes.extend_from_slice(&self.attr.into_bytes());
+    bytes
+  }
+}
+
+/// Response body for `OPEN` (`fuse_open_out`). We have no real file handles,
+/// so the nodeid doubles as `fh`.
+fn open_out_bytes(fh: u64) -> Vec<u8> {
+  let mut bytes = Vec::with_capacity(16);
+  bytes.extend_from_slice(&fh.to_le_bytes());
+  bytes.extend_from_slice(&0u32.to_le_bytes()); // open_flags
+  bytes.extend_from_slice(&0u32.to_le_bytes()); // padding
+  bytes
+}
+
+/// Response body for `WRITE` (`fuse_write_out`).
+fn write_out_bytes(size: u32) -> Vec<u8> {
+  let mut bytes = Vec::with_capacity(8);
+  bytes.extend_from_slice(&size.to_le_bytes());
+  bytes.extend_from_slice(&0u32.to_le_bytes()); // padding
+  bytes
+}
+
+fn read_u32(r: &mut impl Read) -> io::Result<u32> {
+  let mut buf = [0u8; 4];
+  r.read_exact(&mut buf)?;
+  Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64(r: &mut impl Read) -> io::Result<u64> {
+  let mut buf = [0u8; 8];
+  r.read_exact(&mut buf)?;
+  Ok(u64::from_le_bytes(buf))
+}
+
+/// A server adapter wrapping a [`Filesystem`]. One instance owns the mounted
+/// image for the lifetime of the mount and tracks the nodeids the kernel has
+/// handed out.
+pub struct Server<T>
+where
+  T: Read + Write + Seek,
+{
+  fs: Filesystem<T>,
+  nodes: HashMap<u64, String>,
+  next_nodeid: u64,
+}
+
+impl<T> Server<T>
+where
+  T: Read + Write + Seek,
+{
+  pub fn new(fs: Filesystem<T>) -> Self {
+    Server {
+      fs,
+      nodes: HashMap::new(),
+      next_nodeid: ROOT_NODEID + 1,
+    }
+  }
+
+  /// Finds the nodeid already assigned to `name`, or allocates a fresh one.
+  fn nodeid_for(&mut self, name: &str) -> u64 {
+    if let Some((&nodeid, _)) = self.nodes.iter().find(|(_, n)| n.as_str() == name) {
+      return nodeid;
+    }
+
+    let nodeid = self.next_nodeid;
+    self.next_nodeid += 1;
+    self.nodes.insert(nodeid, name.to_owned());
+    nodeid
+  }
+
+  /// Resolves a nodeid back to the name it was issued for.
+  fn name_for(&self, nodeid: u64) -> io::Result<&str> {
+    self
+      .nodes
+      .get(&nodeid)
+      .map(String::as_str)
+      .ok_or_else(|| io::Error::from_raw_os_error(libc::ENOENT))
+  }
+
+  /// Dispatches a single request to its opcode handler and wraps the result
+  /// in a `fuse_out_header`, the way every real FUSE reply is framed.
+  pub fn handle_message(&mut self, header: InHeader, payload: &[u8]) -> Vec<u8> {
+    let body = match Opcode::from_raw(header.opcode) {
+      Some(Opcode::Lookup) => self.lookup(payload),
+      Some(Opcode::GetAttr) => self.getattr(header.nodeid),
+      Some(Opcode::ReadDir) => self.readdir(),
+      Some(Opcode::Read) => self.read(header.nodeid, payload),
+      Some(Opcode::Open) => Ok(open_out_bytes(header.nodeid)),
+      Some(Opcode::Write) => self.write(header.nodeid, payload),
+      Some(Opcode::Create) => self.create(payload),
+      Some(Opcode::Unlink) => self.unlink(header.nodeid, payload),
+      None => Err(io::Error::from_raw_os_error(libc::ENOSYS)),
+    };
+
+    let (error, data) = match body {
+      Ok(data) => (0, data),
+      Err(err) => (-err.raw_os_error().unwrap_or(libc::EIO), Vec::new()),
+    };
+
+    let out_header = OutHeader {
+      len: (16 + data.len()) as u32,
+      error,
+      unique: header.unique,
+    };
+
+    let mut reply = out_header.into_bytes();
+    reply.extend_from_slice(&data);
+    reply
+  }
+
+  /// `LOOKUP` resolves a name (scanned via [`Filesystem::list`]) to a nodeid,
+  /// assigning one if this is the first time the kernel has asked about it.
+  fn lookup(&mut self, payload: &[u8]) -> io::Result<Vec<u8>> {
+    let name = cstr_name(payload);
+    let names = self.fs.list().map_err(into_io)?;
+    if !names.contains(&name) {
+      return Err(io::Error::from_raw_os_error(libc::ENOENT));
+    }
+
+    let size = self.fs.read(name.clone()).map_err(into_io)?.len() as u64;
+    let nodeid = self.nodeid_for(&name);
+
+    Ok(
+      EntryOut {
+        nodeid,
+        attr: Attr {
+          ino: nodeid,
+          size,
+          mode: S_IFREG | 0o644,
+        },
+      }
+      .into_bytes(),
+    )
+  }
+
+  /// `GETATTR` reports directory attrs for the root and file attrs (size
+  /// pulled from the block chain) for anything else.
+  fn getattr(&mut self, nodeid: u64) -> io::Result<Vec<u8>> {
+    let attr = if nodeid == ROOT_NODEID {
+      Attr {
+        ino: ROOT_NODEID,
+        size: 0,
+        mode: S_IFDIR | 0o755,
+      }
+    } else {
+      let name = self.name_for(nodeid)?.to_owned();
+      let size = self.fs.read(name).map_err(into_io)?.len() as u64;
+      Attr {
+        ino: nodeid,
+        size,
+        mode: S_IFREG | 0o644,
+      }
+    };
+
+    Ok(AttrOut { attr }.into_bytes())
+  }
+
+  /// `READDIR` is a thin wrapper over [`Filesystem::list`], returning the names
+  /// joined by NUL bytes rather than as `fuse_dirent` records.
+  fn readdir(&mut self) -> io::Result<Vec<u8>> {
+    let names = self.fs.list().map_err(into_io)?;
+    Ok(names.join("\0").into_bytes())
+  }
+
+  /// `READ` resolves `nodeid` to a name, reads the whole file via
+  /// [`Filesystem::read`], and slices out the requested window.
+  fn read(&mut self, nodeid: u64, payload: &[u8]) -> io::Result<Vec<u8>> {
+    let read_in = ReadIn::from_bytes(payload)?;
+    let name = self.name_for(nodeid)?.to_owned();
+    let data = self.fs.read(name).map_err(into_io)?;
+
+    let start = (read_in.offset as usize).min(data.len());
+    let end = start.saturating_add(read_in.size as usize).min(data.len());
+
+    Ok(data[start..end].to_vec())
+  }
+
+  /// `WRITE` splices the incoming bytes into the file at `offset`, zero-padding
+  /// if the write starts past the current end, and re-inserts the whole file
+  /// since [`Filesystem::insert`] has no partial-write API of its own.
+  fn write(&mut self, nodeid: u64, payload: &[u8]) -> io::Result<Vec<u8>> {
+    if payload.len() < WriteIn::SIZE {
+      return Err(io::Error::from_raw_os_error(libc::EINVAL));
+    }
+
+    let write_in = WriteIn::from_bytes(&payload[..WriteIn::SIZE])?;
+    let data_in = &payload[WriteIn::SIZE..];
+
+    let name = self.name_for(nodeid)?.to_owned();
+    let mut content = self.fs.read(name.clone()).map_err(into_io)?;
+
+    let start = write_in.offset as usize;
+    let end = start + write_in.size as usize;
+    if content.len() < end {
+      content.resize(end, 0);
+    }
+    content[start..end].copy_from_slice(&data_in[..write_in.size as usize]);
+
+    self.fs.insert(name, content).map_err(into_io)?;
+
+    Ok(write_out_bytes(write_in.size))
+  }
+
+  /// `CREATE` inserts an empty file and hands back its nodeid (reusing one
+  /// already issued for the same name, otherwise a fresh one) plus an
+  /// `OPEN` reply, matching the combined entry+open response the kernel
+  /// expects from this opcode.
+  fn create(&mut self, payload: &[u8]) -> io::Result<Vec<u8>> {
+    let name = cstr_name(payload);
+    self
+      .fs
+      .insert(name.clone(), Vec::<u8>::new())
+      .map_err(into_io)?;
+
+    let nodeid = self.nodeid_for(&name);
+    let entry = EntryOut {
+      nodeid,
+      attr: Attr {
+        ino: nodeid,
+        size: 0,
+        mode: S_IFREG | 0o644,
+      },
+    }
+    .into_bytes();
+
+    Ok([entry, open_out_bytes(nodeid)].concat())
+  }
+
+  /// `UNLINK` removes the file and drops any nodeid the kernel had cached
+  /// for it.
+  fn unlink(&mut self, _parent: u64, payload: &[u8]) -> io::Result<Vec<u8>> {
+    let name = cstr_name(payload);
+    self.fs.remove(name.clone()).map_err(into_io)?;
+    self.nodes.retain(|_, n| *n != name);
+    Ok(Vec::new())
+  }
+}
+
+/// Extracts the NUL-terminated name at the front of a request body.
+fn cstr_name(payload: &[u8]) -> String {
+  let end = payload.iter().position(|b| *b == 0).unwrap_or(payload.len());
+  String::from_utf8_lossy(&payload[..end]).into_owned()
+}
+
+fn into_io(err: crate::filesystem::GenericError) -> io::Error {
+  io::Error::other(err)
+}
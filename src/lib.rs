@@ -1,6 +1,11 @@
 use std::io::{self, copy, Cursor, Read, Seek, SeekFrom, Write};
 use thiserror::Error;
 
+pub mod filesystem;
+
+#[cfg(feature = "fuse")]
+pub mod fuse;
+
 #[derive(Error, Debug)]
 pub enum FileSystemError {
   #[error("No more space in the table")]
@@ -8,15 +13,71 @@ pub enum FileSystemError {
 
   #[error("File name is larger than {} bytes", Filesystem::FILENAME_SIZE)]
   FileNameTooLarge,
+
+  #[error("File not found")]
+  FileNotFound,
+
+  #[error("Too many open files (max {})", Filesystem::MAX_OPEN)]
+  TooManyOpenFiles,
+
+  #[error("Invalid file handle")]
+  InvalidHandle,
+
+  #[error("File is not open for this operation")]
+  WrongMode,
+
+  #[error("File exceeds the maximum of {} blocks", Filesystem::MAX_FILE_BLOCKS)]
+  FileTooLarge,
+
+  #[error("No more free blocks on disk")]
+  OutOfSpace,
+
+  #[error("A path component is not a directory")]
+  NotADirectory,
+
+  #[error(transparent)]
+  Io(#[from] io::Error),
+
+  #[error("Too many snapshots (max {})", Filesystem::MAX_SNAPSHOTS)]
+  TooManySnapshots,
+
+  #[error("Snapshot not found")]
+  SnapshotNotFound,
+}
+
+/// The access mode a file is opened with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+  /// Read from the start of the file; writes are rejected.
+  ReadOnly,
+  /// Write fresh content from the start, truncating any existing data.
+  WriteNew,
+  /// Seek the cursor to the end of the file so writes extend it.
+  Append,
+}
+
+/// An opaque handle into the open-file table returned by
+/// [`Filesystem::open_file`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileHandle(usize);
+
+/// The bookkeeping tracked for each open file: where its header lives, the
+/// mode it was opened with, and the cursor offset into its data region.
+#[derive(Debug, Clone, Copy)]
+struct OpenFile {
+  header_addr: usize,
+  cursor: u16,
+  mode: Mode,
 }
 
 #[derive(Debug)]
 /// The header at the top of a virtual disk file
 /// - headers (u8) how many file headers there are
-/// - free_addr (u16) the address of the next free data space
+///
+/// Block allocation is tracked by the on-disk FAT rather than the header, so
+/// the rest of the reserved area is left as padding.
 pub struct FSHeader {
   headers: u8,
-  free_addr: u16,
 }
 
 impl FSHeader {
@@ -25,16 +86,14 @@ impl FSHeader {
     reader.read_exact(&mut headers)?;
     let headers = u8::from_le_bytes(headers);
 
-    let mut free_addr = [0u8; 2];
-    reader.read_exact(&mut free_addr)?;
-    let free_addr = u16::from_le_bytes(free_addr);
-
-    Ok(Self { headers, free_addr })
+    Ok(Self { headers })
   }
 
   pub fn write(&mut self, writer: &mut impl Write) -> io::Result<()> {
     writer.write_all(&self.headers.to_le_bytes())?;
-    writer.write_all(&self.free_addr.to_le_bytes())?;
+
+    // Pad out the reserved header area so the file headers stay aligned.
+    writer.write_all(&[0u8; Filesystem::FS_HEADER_SIZE - 1])?;
 
     Ok(())
   }
@@ -56,24 +115,32 @@ pub enum FileHeaderError {
 }
 
 /// File Header Spec:
-/// - addr of data (u16)
+/// - kind (u8) 0 = empty, 1 = file, 2 = directory
+/// - first block index (u16)
 /// - len of data (u16)
 /// - len of name (u8) (max 16)
 /// - name (char bytes; len = len of name)
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct FileHeader {
-  data_addr: u16,
+  kind: u8,
+  first_block: u16,
   data_len: u16,
   name: String,
 }
 
 impl FileHeader {
   pub fn read(reader: &mut impl Read) -> Result<Self, FileHeaderError> {
-    let mut data_addr = [0u8; 2];
+    let mut kind = [0u8; 1];
+    reader
+      .read_exact(&mut kind)
+      .map_err(FileHeaderError::DataAddress)?;
+    let kind = u8::from_le_bytes(kind);
+
+    let mut first_block = [0u8; 2];
     reader
-      .read_exact(&mut data_addr)
+      .read_exact(&mut first_block)
       .map_err(FileHeaderError::DataAddress)?;
-    let data_addr = u16::from_le_bytes(data_addr);
+    let first_block = u16::from_le_bytes(first_block);
 
     let mut data_len = [0u8; 2];
     reader
@@ -94,20 +161,23 @@ impl FileHeader {
     let name = String::from_utf8(name)?;
 
     Ok(Self {
-      data_addr,
+      kind,
+      first_block,
       data_len,
       name,
     })
   }
 
   pub fn write(&mut self, writer: &mut impl Write) -> io::Result<()> {
-    let data_addr = self.data_addr.to_le_bytes();
+    let kind = self.kind.to_le_bytes();
+    let first_block = self.first_block.to_le_bytes();
     let data_len = self.data_len.to_le_bytes();
     let name_buf = self.name.as_bytes();
 
     let name_len = (name_buf.len() as u8).to_le_bytes();
 
-    writer.write_all(&data_addr)?;
+    writer.write_all(&kind)?;
+    writer.write_all(&first_block)?;
     writer.write_all(&data_len)?;
     writer.write_all(&name_len)?;
     writer.write_all(name_buf)?;
@@ -121,38 +191,104 @@ pub struct Filesystem {
   pub path: Option<String>,
   pub file: Option<std::fs::File>,
   pub memcache: Cursor<Vec<u8>>,
+  open_files: Vec<Option<OpenFile>>,
 }
 
 impl Filesystem {
-  /// The size of the filesystem header (for storing state)
-  pub const FS_HEADER_SIZE: usize = 16;
+  /// The size of the filesystem header (for storing state; the bytes after the
+  /// header count are padding)
+  pub const FS_HEADER_SIZE: usize = 64;
 
   // File Header Spec:
   // ```txt
-  // |            bytes             |
-  // | addr | len | name_len | name |
-  // | 2    | 2   | 1        | 16   |
+  // |                 bytes                 |
+  // | kind | first_block | len | name_len | name |
+  // | 1    | 2           | 2   | 1        | 16   |
   // ```
 
   /// The max size in bytes of a file name
   pub const FILENAME_SIZE: usize = 16;
 
   /// The set size of a file header (alignment)
-  pub const TABLE_ALIGN: usize = Self::FILENAME_SIZE + 5;
+  pub const TABLE_ALIGN: usize = Self::FILENAME_SIZE + 6;
 
   /// The total number of file headers that can be stored
   pub const TOTAL_HEADERS: usize = 10;
 
-  /// The total size of the virtual disk (excluding file data)
+  /// Header slot reserved for the root directory
+  pub const ROOT_SLOT: usize = 0;
+
+  /// `kind` value for an unused header slot
+  pub const KIND_EMPTY: u8 = 0;
+
+  /// `kind` value for a regular file
+  pub const KIND_FILE: u8 = 1;
+
+  /// `kind` value for a directory
+  pub const KIND_DIR: u8 = 2;
+
+  /// The maximum number of files that can be open at once
+  pub const MAX_OPEN: usize = 16;
+
+  /// The size of the filesystem header plus the header table, i.e. everything
+  /// before the FAT
   pub const TABLE_SIZE: usize =
     Self::TABLE_ALIGN * Self::TOTAL_HEADERS + Self::FS_HEADER_SIZE;
 
+  /// The size of a single data block, in bytes
+  pub const BLOCK_SIZE: usize = 64;
+
+  /// The number of data blocks in the data region
+  pub const NUM_BLOCKS: usize = 256;
+
+  /// The maximum number of blocks a single file can span
+  pub const MAX_FILE_BLOCKS: usize = 64;
+
+  /// FAT sentinel marking the end of a block chain
+  pub const FAT_EOC: u16 = 0xFFFF;
+
+  /// FAT sentinel marking a free (unallocated) block
+  pub const FAT_FREE: u16 = 0xFFFE;
+
+  /// Header value for a file with no data blocks yet
+  pub const NO_BLOCK: u16 = 0xFFFF;
+
+  /// The size of the on-disk FAT, in bytes
+  pub const FAT_SIZE: usize = Self::NUM_BLOCKS * 2;
+
+  /// The maximum number of snapshots that can be stored at once
+  pub const MAX_SNAPSHOTS: usize = 4;
+
+  /// The size of one snapshot's header-table copy, in bytes
+  pub const SNAPSHOT_TABLE_SIZE: usize =
+    Self::TABLE_ALIGN * Self::TOTAL_HEADERS;
+
+  /// The on-disk size of one snapshot slot: a one-byte in-use flag plus its
+  /// header-table copy
+  pub const SNAPSHOT_SLOT_SIZE: usize = 1 + Self::SNAPSHOT_TABLE_SIZE;
+
+  /// The size of the on-disk per-block refcount table, in bytes
+  pub const REFCOUNT_SIZE: usize = Self::NUM_BLOCKS * 2;
+
+  /// The offset of the block refcount table (past the header table and FAT)
+  pub const REFCOUNT_OFFSET: usize = Self::TABLE_SIZE + Self::FAT_SIZE;
+
+  /// The offset of the snapshot region (past the refcount table)
+  pub const SNAPSHOTS_OFFSET: usize =
+    Self::REFCOUNT_OFFSET + Self::REFCOUNT_SIZE;
+
+  /// The offset of the first data block (past the header table, FAT, refcount
+  /// table, and snapshot region)
+  pub const DATA_OFFSET: usize =
+    Self::SNAPSHOTS_OFFSET + Self::MAX_SNAPSHOTS * Self::SNAPSHOT_SLOT_SIZE;
+
   pub fn new(path: Option<&str>) -> Self {
     if let Some(path) = path {
       let file = std::fs::OpenOptions::new()
         .create(true)
         .write(true)
         .read(true)
+        .truncate(false)
         .open(path)
         .unwrap();
 
@@ -160,12 +296,14 @@ impl Filesystem {
         path: Some(path.to_string()),
         file: Some(file),
         memcache: Cursor::new(vec![]),
+        open_files: vec![],
       }
     } else {
       Self {
         path: None,
         file: None,
         memcache: Cursor::new(vec![]),
+        open_files: vec![],
       }
     }
   }
@@ -201,143 +339,949 @@ impl Filesystem {
     self.memcache.set_position(0);
     self.memcache.write_all(&buf).unwrap();
 
+    // Mark every FAT entry free.
+    for i in 0..Self::NUM_BLOCKS {
+      self.write_fat_entry(i as u16, Self::FAT_FREE);
+    }
+
+    // Zero the refcount table and snapshot region so every block reads as
+    // unreferenced and every snapshot slot reads as unused.
+    let tail_size = Self::DATA_OFFSET - Self::REFCOUNT_OFFSET;
+    self.memcache.set_position(Self::REFCOUNT_OFFSET as u64);
+    self.memcache.write_all(&vec![0u8; tail_size]).unwrap();
+
+    // Create the root directory at its fixed slot as the tree's entry point.
+    let mut root = FileHeader {
+      kind: Self::KIND_DIR,
+      first_block: Self::NO_BLOCK,
+      data_len: 0,
+      name: "/".to_string(),
+    };
+    self.write_header_slot(Self::ROOT_SLOT, &mut root);
+
     self.flush();
   }
 
-  /// Scans the header table into memory
-  fn scan_headers(&mut self) -> Result<Vec<FileHeader>, FileHeaderError> {
-    let mut headers: Vec<FileHeader> = vec![];
+  /// The byte address of a header slot in the table.
+  fn slot_addr(slot: usize) -> usize {
+    Self::FS_HEADER_SIZE + slot * Self::TABLE_ALIGN
+  }
+
+  /// Reads the header at a given slot index.
+  fn read_header_slot(&mut self, slot: usize) -> FileHeader {
+    self.read_header_at(Self::slot_addr(slot))
+  }
+
+  /// Writes a header into a given slot index.
+  fn write_header_slot(&mut self, slot: usize, header: &mut FileHeader) {
+    self.memcache.set_position(Self::slot_addr(slot) as u64);
+    header.write(&mut self.memcache).unwrap();
+  }
+
+  /// Finds the first unused header slot.
+  fn alloc_header_slot(&mut self) -> Option<usize> {
+    (0..Self::TOTAL_HEADERS)
+      .find(|&slot| self.read_header_slot(slot).kind == Self::KIND_EMPTY)
+  }
 
-    // Skip the filesystem header
-    self.memcache.set_position(Self::FS_HEADER_SIZE as u64);
+  /// The byte address of a snapshot slot's in-use flag.
+  fn snapshot_addr(id: usize) -> usize {
+    Self::SNAPSHOTS_OFFSET + id * Self::SNAPSHOT_SLOT_SIZE
+  }
 
-    for i in 0..Self::TOTAL_HEADERS {
-      // Set the cursor position to the start of the header
-      self.memcache.set_position(
-        (Self::FS_HEADER_SIZE as u64) + (Self::TABLE_ALIGN as u64) * (i as u64),
-      );
-      let header = FileHeader::read(&mut self.memcache);
+  /// The byte address of a header slot within a snapshot's table copy.
+  fn snapshot_header_addr(id: usize, slot: usize) -> usize {
+    Self::snapshot_addr(id) + 1 + slot * Self::TABLE_ALIGN
+  }
+
+  /// Whether a snapshot slot currently holds a live snapshot.
+  fn snapshot_in_use(&mut self, id: usize) -> bool {
+    self.memcache.set_position(Self::snapshot_addr(id) as u64);
+    let mut flag = [0u8; 1];
+    self.memcache.read_exact(&mut flag).unwrap();
+    flag[0] != 0
+  }
 
-      headers.push(header?);
+  /// Marks a snapshot slot as occupied or free.
+  fn set_snapshot_in_use(&mut self, id: usize, used: bool) {
+    self.memcache.set_position(Self::snapshot_addr(id) as u64);
+    self.memcache.write_all(&[used as u8]).unwrap();
+  }
+
+  /// Finds the first unused snapshot slot.
+  fn alloc_snapshot_slot(&mut self) -> Option<usize> {
+    (0..Self::MAX_SNAPSHOTS).find(|&id| !self.snapshot_in_use(id))
+  }
+
+  /// Reads a header from a snapshot's table copy.
+  fn read_snapshot_header(&mut self, id: usize, slot: usize) -> FileHeader {
+    self.read_header_at(Self::snapshot_header_addr(id, slot))
+  }
+
+  /// Writes a header into a snapshot's table copy.
+  fn write_snapshot_header(
+    &mut self,
+    id: usize,
+    slot: usize,
+    header: &mut FileHeader,
+  ) {
+    self
+      .memcache
+      .set_position(Self::snapshot_header_addr(id, slot) as u64);
+    header.write(&mut self.memcache).unwrap();
+  }
+
+  /// Creates a snapshot of the current header table, retaining every block
+  /// it references instead of copying data. Returns the new snapshot's id.
+  pub fn snapshot(&mut self) -> Result<usize, FileSystemError> {
+    let id = self
+      .alloc_snapshot_slot()
+      .ok_or(FileSystemError::TooManySnapshots)?;
+
+    for slot in 0..Self::TOTAL_HEADERS {
+      let mut header = self.read_header_slot(slot);
+      if header.kind != Self::KIND_EMPTY && header.first_block != Self::NO_BLOCK
+      {
+        self.retain_chain(header.first_block);
+      }
+      self.write_snapshot_header(id, slot, &mut header);
+    }
+
+    self.set_snapshot_in_use(id, true);
+    self.flush();
+
+    Ok(id)
+  }
+
+  /// Restores the header table to a previous snapshot's contents. Blocks the
+  /// live table stops referencing are decremented (and reclaimed once no
+  /// longer shared); the restored chains are retained again for the live
+  /// table's new ownership of them.
+  pub fn restore_snapshot(&mut self, id: usize) -> Result<(), FileSystemError> {
+    if !self.snapshot_in_use(id) {
+      return Err(FileSystemError::SnapshotNotFound);
+    }
+
+    for slot in 0..Self::TOTAL_HEADERS {
+      let live = self.read_header_slot(slot);
+      if live.kind != Self::KIND_EMPTY && live.first_block != Self::NO_BLOCK {
+        self.free_chain(live.first_block);
+      }
+
+      let mut restored = self.read_snapshot_header(id, slot);
+      if restored.kind != Self::KIND_EMPTY
+        && restored.first_block != Self::NO_BLOCK
+      {
+        self.retain_chain(restored.first_block);
+      }
+      self.write_header_slot(slot, &mut restored);
     }
 
-    Ok(headers)
+    self.flush();
+    Ok(())
   }
 
-  /// Gets a file header from the filesystem
-  fn get_file_header(
+  /// Deletes a snapshot, decrementing the refcounts of every block it
+  /// references and reclaiming any that drop to zero.
+  pub fn delete_snapshot(&mut self, id: usize) -> Result<(), FileSystemError> {
+    if !self.snapshot_in_use(id) {
+      return Err(FileSystemError::SnapshotNotFound);
+    }
+
+    for slot in 0..Self::TOTAL_HEADERS {
+      let header = self.read_snapshot_header(id, slot);
+      if header.kind != Self::KIND_EMPTY && header.first_block != Self::NO_BLOCK
+      {
+        self.free_chain(header.first_block);
+      }
+    }
+
+    self.set_snapshot_in_use(id, false);
+    self.flush();
+
+    Ok(())
+  }
+
+  /// Parses a directory's `(name, slot)` entries out of its block chain.
+  fn read_dir_entries(&mut self, dir: &FileHeader) -> Vec<(String, u8)> {
+    let mut data = self.read_chain(dir.first_block);
+    data.truncate(dir.data_len as usize);
+
+    let mut entries = vec![];
+    let mut i = 0;
+    while i < data.len() {
+      let name_len = data[i] as usize;
+      i += 1;
+      let name = String::from_utf8_lossy(&data[i..i + name_len]).into_owned();
+      i += name_len;
+      let slot = data[i];
+      i += 1;
+      entries.push((name, slot));
+    }
+
+    entries
+  }
+
+  /// Serializes and writes a directory's entries, replacing its block chain.
+  fn write_dir(&mut self, dir_slot: usize, entries: &[(String, u8)]) {
+    let mut bytes = vec![];
+    for (name, slot) in entries {
+      bytes.push(name.len() as u8);
+      bytes.extend_from_slice(name.as_bytes());
+      bytes.push(*slot);
+    }
+
+    let mut dir = self.read_header_slot(dir_slot);
+    if dir.first_block != Self::NO_BLOCK {
+      self.free_chain(dir.first_block);
+    }
+    dir.first_block = self.write_chain(&bytes).unwrap();
+    dir.data_len = bytes.len() as u16;
+    self.write_header_slot(dir_slot, &mut dir);
+  }
+
+  /// Resolves a `/`-separated path to its header slot and header, walking the
+  /// directory tree from the root.
+  fn resolve(&mut self, path: &str) -> Option<(usize, FileHeader)> {
+    let mut slot = Self::ROOT_SLOT;
+    let mut header = self.read_header_slot(slot);
+
+    for component in path.split('/').filter(|c| !c.is_empty()) {
+      if header.kind != Self::KIND_DIR {
+        return None;
+      }
+      let entries = self.read_dir_entries(&header);
+      match entries.iter().find(|(name, _)| name == component) {
+        Some((_, next)) => {
+          slot = *next as usize;
+          header = self.read_header_slot(slot);
+        }
+        None => return None,
+      }
+    }
+
+    Some((slot, header))
+  }
+
+  /// Creates a directory named `name` in the directory at `parent_slot`,
+  /// returning the new directory's slot.
+  fn create_dir_in(
     &mut self,
-    filename: String,
-  ) -> Result<Option<FileHeader>, FileHeaderError> {
-    let headers = self.scan_headers()?;
+    parent_slot: usize,
+    name: &str,
+  ) -> Result<usize, FileSystemError> {
+    let slot = self
+      .alloc_header_slot()
+      .ok_or(FileSystemError::NoMoreSpaceInTable)?;
+
+    let mut dir = FileHeader {
+      kind: Self::KIND_DIR,
+      first_block: Self::NO_BLOCK,
+      data_len: 0,
+      name: name.to_string(),
+    };
+    self.write_header_slot(slot, &mut dir);
+
+    let parent = self.read_header_slot(parent_slot);
+    let mut entries = self.read_dir_entries(&parent);
+    entries.push((name.to_string(), slot as u8));
+    self.write_dir(parent_slot, &entries);
 
-    for header in headers {
-      if header.name == filename {
-        return Ok(Some(header));
+    Ok(slot)
+  }
+
+  /// Walks a path to its parent directory, optionally auto-creating missing
+  /// intermediate directories, and returns `(parent_slot, final_component)`.
+  fn resolve_parent(
+    &mut self,
+    path: &str,
+    create: bool,
+  ) -> Result<(usize, String), FileSystemError> {
+    let components: Vec<&str> =
+      path.split('/').filter(|c| !c.is_empty()).collect();
+    let (name, dirs) = components
+      .split_last()
+      .ok_or(FileSystemError::FileNotFound)?;
+
+    let mut slot = Self::ROOT_SLOT;
+    for dir in dirs {
+      let header = self.read_header_slot(slot);
+      if header.kind != Self::KIND_DIR {
+        return Err(FileSystemError::NotADirectory);
+      }
+      let entries = self.read_dir_entries(&header);
+      slot = match entries.iter().find(|(n, _)| n == dir) {
+        Some((_, next)) => {
+          if self.read_header_slot(*next as usize).kind != Self::KIND_DIR {
+            return Err(FileSystemError::NotADirectory);
+          }
+          *next as usize
+        }
+        None if create => self.create_dir_in(slot, dir)?,
+        None => return Err(FileSystemError::FileNotFound),
+      };
+    }
+
+    Ok((slot, name.to_string()))
+  }
+
+  /// Creates a directory (and any missing parents) at `path`.
+  pub fn mkdir(&mut self, path: String) -> Result<(), FileSystemError> {
+    let (parent_slot, name) = self.resolve_parent(&path, true)?;
+
+    let parent = self.read_header_slot(parent_slot);
+    let entries = self.read_dir_entries(&parent);
+    if entries.iter().any(|(n, _)| n == &name) {
+      return Ok(());
+    }
+
+    self.create_dir_in(parent_slot, &name)?;
+    Ok(())
+  }
+
+  /// Lists the headers of every entry in the directory at `path`.
+  pub fn list_dir(
+    &mut self,
+    path: String,
+  ) -> Result<Vec<FileHeader>, FileSystemError> {
+    let (_, dir) = self.resolve(&path).ok_or(FileSystemError::FileNotFound)?;
+    if dir.kind != Self::KIND_DIR {
+      return Err(FileSystemError::NotADirectory);
+    }
+
+    let entries = self.read_dir_entries(&dir);
+    Ok(
+      entries
+        .iter()
+        .map(|(_, slot)| self.read_header_slot(*slot as usize))
+        .collect(),
+    )
+  }
+
+  /// Reads a single FAT entry.
+  fn read_fat_entry(&mut self, block: u16) -> u16 {
+    self
+      .memcache
+      .set_position(Self::TABLE_SIZE as u64 + block as u64 * 2);
+    let mut entry = [0u8; 2];
+    self.memcache.read_exact(&mut entry).unwrap();
+    u16::from_le_bytes(entry)
+  }
+
+  /// Writes a single FAT entry.
+  fn write_fat_entry(&mut self, block: u16, value: u16) {
+    self
+      .memcache
+      .set_position(Self::TABLE_SIZE as u64 + block as u64 * 2);
+    self.memcache.write_all(&value.to_le_bytes()).unwrap();
+  }
+
+  /// Reads a single block's refcount.
+  fn read_refcount(&mut self, block: u16) -> u16 {
+    self
+      .memcache
+      .set_position(Self::REFCOUNT_OFFSET as u64 + block as u64 * 2);
+    let mut count = [0u8; 2];
+    self.memcache.read_exact(&mut count).unwrap();
+    u16::from_le_bytes(count)
+  }
+
+  /// Writes a single block's refcount.
+  fn write_refcount(&mut self, block: u16, count: u16) {
+    self
+      .memcache
+      .set_position(Self::REFCOUNT_OFFSET as u64 + block as u64 * 2);
+    self.memcache.write_all(&count.to_le_bytes()).unwrap();
+  }
+
+  /// Claims the first free block, marking it end-of-chain with a refcount of
+  /// one, or returns `None` when the disk is full.
+  fn alloc_block(&mut self) -> Option<u16> {
+    for i in 0..Self::NUM_BLOCKS as u16 {
+      if self.read_fat_entry(i) == Self::FAT_FREE {
+        self.write_fat_entry(i, Self::FAT_EOC);
+        self.write_refcount(i, 1);
+        return Some(i);
+      }
+    }
+
+    None
+  }
+
+  /// Walks a block chain and increments every block's refcount, letting an
+  /// extra owner (e.g. a snapshot) share it instead of copying the data.
+  fn retain_chain(&mut self, first_block: u16) {
+    let mut block = first_block;
+    while (block as usize) < Self::NUM_BLOCKS {
+      let count = self.read_refcount(block) + 1;
+      self.write_refcount(block, count);
+      block = self.read_fat_entry(block);
+    }
+  }
+
+  /// Walks a block chain, decrementing each block's refcount, and reclaims
+  /// only the blocks whose count reaches zero — i.e. that no snapshot still
+  /// shares.
+  fn free_chain(&mut self, first_block: u16) {
+    let mut block = first_block;
+    while (block as usize) < Self::NUM_BLOCKS {
+      let next = self.read_fat_entry(block);
+      let count = self.read_refcount(block).saturating_sub(1);
+      self.write_refcount(block, count);
+      if count == 0 {
+        self.write_fat_entry(block, Self::FAT_FREE);
       }
+      block = next;
+    }
+  }
+
+  /// Reads the raw (block-aligned) bytes of a chain starting at `first_block`.
+  fn read_chain(&mut self, first_block: u16) -> Vec<u8> {
+    let mut bytes = vec![];
+    let mut block = first_block;
+    while (block as usize) < Self::NUM_BLOCKS {
+      let offset = Self::DATA_OFFSET + block as usize * Self::BLOCK_SIZE;
+      let mut buf = vec![0u8; Self::BLOCK_SIZE];
+      self.memcache.set_position(offset as u64);
+      self.memcache.read_exact(&mut buf).unwrap();
+      bytes.extend_from_slice(&buf);
+      block = self.read_fat_entry(block);
+    }
+
+    bytes
+  }
+
+  /// Streams the first `data_len` bytes of a chain directly into `writer`,
+  /// one block at a time, without buffering the whole chain.
+  fn read_chain_stream(
+    &mut self,
+    header: &FileHeader,
+    writer: &mut impl Write,
+  ) -> Result<(), FileSystemError> {
+    let mut remaining = header.data_len as usize;
+    let mut block = header.first_block;
+
+    while remaining > 0 && (block as usize) < Self::NUM_BLOCKS {
+      let offset = Self::DATA_OFFSET + block as usize * Self::BLOCK_SIZE;
+      let take = remaining.min(Self::BLOCK_SIZE);
+
+      self.memcache.set_position(offset as u64);
+      io::copy(&mut (&mut self.memcache).take(take as u64), writer)?;
+
+      remaining -= take;
+      block = self.read_fat_entry(block);
     }
 
-    Ok(None)
+    Ok(())
   }
 
-  /// Gets the address of a file header from the filesystem
+  /// Gets a file header by walking the directory tree for `path`.
+  fn get_file_header(
+    &mut self,
+    path: String,
+  ) -> Result<Option<FileHeader>, FileHeaderError> {
+    match self.resolve(&path) {
+      Some((_, header)) if header.kind == Self::KIND_FILE => Ok(Some(header)),
+      _ => Ok(None),
+    }
+  }
+
+  /// Gets the table address of a file header by walking the directory tree
+  /// for `path`.
   fn get_file_header_addr(
     &mut self,
-    filename: String,
+    path: String,
   ) -> Result<Option<usize>, FileHeaderError> {
-    let headers = self.scan_headers()?;
-
-    for (i, header) in headers.iter().enumerate() {
-      if header.name == filename {
-        return Ok(Some(i * Self::TABLE_ALIGN + Self::FS_HEADER_SIZE));
+    match self.resolve(&path) {
+      Some((slot, header)) if header.kind == Self::KIND_FILE => {
+        Ok(Some(Self::slot_addr(slot)))
       }
+      _ => Ok(None),
     }
+  }
 
-    Ok(None)
+  /// Reads the raw bytes of a file given its header by walking its block
+  /// chain.
+  ///
+  /// Only exercised from tests; production reads go through
+  /// [`Self::read_file_stream`].
+  #[cfg(test)]
+  fn get_file_data_bytes(&mut self, header: FileHeader) -> Vec<u8> {
+    let mut data = self.read_chain(header.first_block);
+    data.truncate(header.data_len as usize);
+    data
   }
 
-  /// Reads the data of a file given a file header
+  /// Reads the data of a file given a file header as UTF-8 text. Thin
+  /// wrapper over [`Self::get_file_data_bytes`].
+  #[cfg(test)]
   fn get_file_data(
     &mut self,
     header: FileHeader,
   ) -> Result<String, Box<dyn std::error::Error>> {
-    let mut data = vec![0u8; header.data_len as usize];
-    self
-      .memcache
-      .set_position(header.data_addr as u64 + Filesystem::TABLE_SIZE as u64);
-    self.memcache.read_exact(&mut data)?;
+    Ok(String::from_utf8(self.get_file_data_bytes(header))?)
+  }
+
+  /// Resolves (auto-creating missing intermediate directories) the header
+  /// slot a write to `path` should land in: the file's existing slot when
+  /// overwriting (freeing its old block chain up front), or a freshly
+  /// allocated slot registered in the parent directory's entries.
+  fn prepare_file_slot(
+    &mut self,
+    path: &str,
+  ) -> Result<(usize, String, FSHeader), FileSystemError> {
+    let (parent_slot, name) = self.resolve_parent(path, true)?;
+
+    if name.len() > Self::FILENAME_SIZE {
+      return Err(FileSystemError::FileNameTooLarge);
+    }
+
+    // Read the filesystem header
+    self.memcache.set_position(0);
+    let mut fs_header = FSHeader::read(&mut self.memcache).unwrap();
+
+    let parent = self.read_header_slot(parent_slot);
+    let mut entries = self.read_dir_entries(&parent);
+    let existing = entries.iter().find(|(n, _)| n == &name).map(|(_, s)| *s);
+
+    // Resolve the header slot: reuse the existing one, or allocate a fresh one.
+    let slot = match existing {
+      Some(slot) => {
+        let slot = slot as usize;
+        let old = self.read_header_slot(slot);
+        if old.kind == Self::KIND_DIR {
+          return Err(FileSystemError::NotADirectory);
+        }
+        // When overwriting, free the old block chain so its blocks are
+        // reclaimed.
+        if old.first_block != Self::NO_BLOCK {
+          self.free_chain(old.first_block);
+        }
+        slot
+      }
+      None => {
+        let slot = self
+          .alloc_header_slot()
+          .ok_or(FileSystemError::NoMoreSpaceInTable)?;
+        entries.push((name.clone(), slot as u8));
+        self.write_dir(parent_slot, &entries);
+        fs_header.headers += 1;
+        slot
+      }
+    };
 
-    Ok(String::from_utf8(data)?)
+    Ok((slot, name, fs_header))
   }
 
-  /// Create a file in the filesystem
+  /// Writes the header for a slot prepared by [`Self::prepare_file_slot`] and
+  /// flushes it to disk.
+  fn finish_file_write(
+    &mut self,
+    slot: usize,
+    mut fs_header: FSHeader,
+    mut file_header: FileHeader,
+  ) -> FileHeader {
+    self.write_header_slot(slot, &mut file_header);
+
+    self.memcache.seek(SeekFrom::Start(0)).unwrap();
+    fs_header.write(&mut self.memcache).unwrap();
+
+    self.flush();
+    file_header
+  }
+
+  /// Create a file at `path` from raw bytes, auto-creating any missing
+  /// intermediate directories and overwriting an existing file's content in
+  /// place. Stores the bytes as-is, with no UTF-8 requirement.
+  pub fn create_file_bytes(
+    &mut self,
+    path: String,
+    content: &[u8],
+  ) -> Result<FileHeader, FileSystemError> {
+    let (slot, name, fs_header) = self.prepare_file_slot(&path)?;
+
+    // Allocate and fill a block chain for the content.
+    let first_block = self.write_chain(content)?;
+
+    let file_header = FileHeader {
+      kind: Self::KIND_FILE,
+      first_block,
+      data_len: content.len() as u16,
+      name,
+    };
+
+    Ok(self.finish_file_write(slot, fs_header, file_header))
+  }
+
+  /// Create a file at `path` from UTF-8 text. Thin wrapper over
+  /// [`Self::create_file_bytes`].
   pub fn create_file(
     &mut self,
-    filename: String,
+    path: String,
     content: String,
   ) -> Result<FileHeader, FileSystemError> {
-    if filename.len() > Self::FILENAME_SIZE {
-      return Err(FileSystemError::FileNameTooLarge);
+    self.create_file_bytes(path, content.as_bytes())
+  }
+
+  /// Create a file at `path` by streaming `reader` through in `BLOCK_SIZE`
+  /// chunks directly into `memcache`, without buffering the whole payload.
+  pub fn write_file_stream(
+    &mut self,
+    path: String,
+    reader: &mut impl Read,
+  ) -> Result<FileHeader, FileSystemError> {
+    let (slot, name, fs_header) = self.prepare_file_slot(&path)?;
+
+    let (first_block, len) = self.write_chain_stream(reader)?;
+
+    let file_header = FileHeader {
+      kind: Self::KIND_FILE,
+      first_block,
+      data_len: len as u16,
+      name,
+    };
+
+    Ok(self.finish_file_write(slot, fs_header, file_header))
+  }
+
+  /// Streams the bytes of the file at `path` into `writer` one block at a
+  /// time directly from `memcache`, without buffering the whole file.
+  pub fn read_file_stream(
+    &mut self,
+    path: String,
+    writer: &mut impl Write,
+  ) -> Result<(), FileSystemError> {
+    let (_, header) = self
+      .resolve(&path)
+      .filter(|(_, h)| h.kind == Self::KIND_FILE)
+      .ok_or(FileSystemError::FileNotFound)?;
+
+    self.read_chain_stream(&header, writer)
+  }
+
+  /// Delete a file, freeing its header slot, reclaiming its block chain, and
+  /// removing it from its parent directory's entries.
+  pub fn delete_file(&mut self, path: String) -> Result<(), FileSystemError> {
+    let (parent_slot, name) = self.resolve_parent(&path, false)?;
+
+    let parent = self.read_header_slot(parent_slot);
+    let mut entries = self.read_dir_entries(&parent);
+    let pos = entries
+      .iter()
+      .position(|(n, _)| n == &name)
+      .ok_or(FileSystemError::FileNotFound)?;
+    let slot = entries[pos].1 as usize;
+
+    let header = self.read_header_slot(slot);
+    if header.kind != Self::KIND_FILE {
+      return Err(FileSystemError::FileNotFound);
     }
 
-    // Read the filesystem header
+    if header.first_block != Self::NO_BLOCK {
+      self.free_chain(header.first_block);
+    }
+
+    entries.remove(pos);
+    self.write_dir(parent_slot, &entries);
+
     self.memcache.set_position(0);
     let mut fs_header = FSHeader::read(&mut self.memcache).unwrap();
+    if fs_header.headers > 0 {
+      fs_header.headers -= 1;
+    }
+
+    // Clear the header slot so it reads as empty and can be reused.
+    self.memcache.set_position(Self::slot_addr(slot) as u64);
+    self
+      .memcache
+      .write_all(&[0u8; Filesystem::TABLE_ALIGN])
+      .unwrap();
+
+    self.memcache.set_position(0);
+    fs_header.write(&mut self.memcache).unwrap();
+
+    self.flush();
+    Ok(())
+  }
 
-    // Check if we have reached max headers
-    if fs_header.headers >= Filesystem::TOTAL_HEADERS as u8 {
-      return Err(FileSystemError::NoMoreSpaceInTable);
+  /// Allocates a fresh block chain and writes `bytes` into it in `BLOCK_SIZE`
+  /// chunks, returning the first block index ([`Self::NO_BLOCK`] when empty).
+  fn write_chain(&mut self, bytes: &[u8]) -> Result<u16, FileSystemError> {
+    if bytes.is_empty() {
+      return Ok(Self::NO_BLOCK);
     }
 
-    // Calculate the address we will write the header to
-    let mut header_addr = fs_header.headers as usize * Filesystem::TABLE_ALIGN
-      + Filesystem::FS_HEADER_SIZE;
+    let chunks: Vec<&[u8]> = bytes.chunks(Self::BLOCK_SIZE).collect();
+    if chunks.len() > Self::MAX_FILE_BLOCKS {
+      return Err(FileSystemError::FileTooLarge);
+    }
 
-    // Calculate the address we will write the data to
-    let data_addr = fs_header.free_addr as usize;
+    // Claim the blocks up front so a partial chain isn't left on failure.
+    let mut blocks = Vec::with_capacity(chunks.len());
+    for _ in 0..chunks.len() {
+      match self.alloc_block() {
+        Some(block) => blocks.push(block),
+        None => {
+          for block in &blocks {
+            self.write_fat_entry(*block, Self::FAT_FREE);
+          }
+          return Err(FileSystemError::OutOfSpace);
+        }
+      }
+    }
 
-    // Calculate the start of the data blocks
-    let data_offset = Filesystem::TABLE_SIZE;
+    for (i, block) in blocks.iter().enumerate() {
+      // Write a full, zero-padded block so the data region stays aligned.
+      let mut buf = vec![0u8; Self::BLOCK_SIZE];
+      buf[..chunks[i].len()].copy_from_slice(chunks[i]);
+      let offset = Self::DATA_OFFSET + *block as usize * Self::BLOCK_SIZE;
+      self.memcache.set_position(offset as u64);
+      self.memcache.write_all(&buf).unwrap();
 
-    // Check if the file already exists
-    let existing_header_addr =
-      self.get_file_header_addr(filename.clone()).unwrap();
-    if let Some(addr) = existing_header_addr {
-      header_addr = addr;
+      let next = blocks.get(i + 1).copied().unwrap_or(Self::FAT_EOC);
+      self.write_fat_entry(*block, next);
     }
 
-    // Create the file header
-    let mut file_header = FileHeader {
-      data_addr: data_addr as u16,
-      data_len: content.len() as u16,
-      name: filename,
+    Ok(blocks[0])
+  }
+
+  /// Streams `reader` into a fresh block chain one block at a time, copying
+  /// each chunk directly into `memcache` via [`io::copy`] rather than
+  /// buffering the whole payload. Returns the first block index
+  /// ([`Self::NO_BLOCK`] when empty) and the total number of bytes copied.
+  fn write_chain_stream(
+    &mut self,
+    reader: &mut impl Read,
+  ) -> Result<(u16, usize), FileSystemError> {
+    let mut blocks: Vec<u16> = vec![];
+    let mut total = 0usize;
+
+    loop {
+      if blocks.len() >= Self::MAX_FILE_BLOCKS {
+        for block in &blocks {
+          self.write_fat_entry(*block, Self::FAT_FREE);
+        }
+        return Err(FileSystemError::FileTooLarge);
+      }
+
+      let block = match self.alloc_block() {
+        Some(block) => block,
+        None => {
+          for block in &blocks {
+            self.write_fat_entry(*block, Self::FAT_FREE);
+          }
+          return Err(FileSystemError::OutOfSpace);
+        }
+      };
+
+      let offset = Self::DATA_OFFSET + block as usize * Self::BLOCK_SIZE;
+      self.memcache.set_position(offset as u64);
+      let written = io::copy(
+        &mut reader.by_ref().take(Self::BLOCK_SIZE as u64),
+        &mut self.memcache,
+      )? as usize;
+
+      if written == 0 {
+        // The stream was already exhausted; release the block we just
+        // claimed for it.
+        self.write_fat_entry(block, Self::FAT_FREE);
+        break;
+      }
+
+      // Data left over after `MAX_FILE_BLOCKS` blocks makes the file too large.
+      if blocks.len() >= Self::MAX_FILE_BLOCKS {
+        self.write_fat_entry(block, Self::FAT_FREE);
+        for block in &blocks {
+          self.write_fat_entry(*block, Self::FAT_FREE);
+        }
+        return Err(FileSystemError::FileTooLarge);
+      }
+
+      // Pad a short final block so the data region stays aligned.
+      if written < Self::BLOCK_SIZE {
+        self
+          .memcache
+          .write_all(&vec![0u8; Self::BLOCK_SIZE - written])
+          .unwrap();
+      }
+
+      if let Some(prev) = blocks.last() {
+        self.write_fat_entry(*prev, block);
+      }
+      blocks.push(block);
+      total += written;
+
+      if written < Self::BLOCK_SIZE {
+        break;
+      }
+    }
+
+    let first_block = blocks.first().copied().unwrap_or(Self::NO_BLOCK);
+    Ok((first_block, total))
+  }
+
+  /// Open a file at `path` in the given mode, returning a handle that tracks
+  /// a cursor into the file's data region.
+  ///
+  /// `ReadOnly` and `Append` require the file to already exist; `WriteNew`
+  /// creates (or truncates) it, auto-creating missing intermediate
+  /// directories. Fails with [`FileSystemError::TooManyOpenFiles`] once
+  /// [`Filesystem::MAX_OPEN`] handles are live.
+  pub fn open_file(
+    &mut self,
+    path: String,
+    mode: Mode,
+  ) -> Result<FileHandle, FileSystemError> {
+    let open_count = self.open_files.iter().filter(|s| s.is_some()).count();
+    if open_count >= Self::MAX_OPEN {
+      return Err(FileSystemError::TooManyOpenFiles);
+    }
+
+    // Resolve (or, for WriteNew, create) the file header.
+    if mode == Mode::WriteNew
+      && self.get_file_header_addr(path.clone()).unwrap().is_none()
+    {
+      self.create_file(path.clone(), String::new())?;
+    }
+
+    let header_addr = self
+      .get_file_header_addr(path.clone())
+      .unwrap()
+      .ok_or(FileSystemError::FileNotFound)?;
+    let mut header = self.get_file_header(path).unwrap().unwrap();
+
+    // WriteNew truncates any existing data up front, so the first write
+    // starts from an empty file instead of appending past the old content.
+    if mode == Mode::WriteNew && header.data_len > 0 {
+      if header.first_block != Self::NO_BLOCK {
+        self.free_chain(header.first_block);
+      }
+      header.first_block = Self::NO_BLOCK;
+      header.data_len = 0;
+
+      self.memcache.set_position(header_addr as u64);
+      header.write(&mut self.memcache).unwrap();
+      self.flush();
+    }
+
+    let cursor = match mode {
+      Mode::ReadOnly | Mode::WriteNew => 0,
+      Mode::Append => header.data_len,
+    };
+
+    let open = OpenFile {
+      header_addr,
+      cursor,
+      mode,
     };
 
-    // Write the header
-    self.memcache.set_position(header_addr as u64);
-    file_header.write(&mut self.memcache).unwrap();
+    // Reuse a freed slot if one exists, otherwise grow the table.
+    let index = match self.open_files.iter().position(|s| s.is_none()) {
+      Some(index) => {
+        self.open_files[index] = Some(open);
+        index
+      }
+      None => {
+        self.open_files.push(Some(open));
+        self.open_files.len() - 1
+      }
+    };
 
-    // Write the data
-    self.memcache.set_position((data_addr + data_offset) as u64);
-    self.memcache.write_all(content.as_bytes()).unwrap();
+    Ok(FileHandle(index))
+  }
 
-    // Update the filesystem header
-    if existing_header_addr.is_none() {
-      // If we updated the header, we don't need to increment the header count
-      fs_header.headers += 1;
+  /// Reads from an open file into `buf`, returning the number of bytes read and
+  /// advancing the cursor. Bounded by the file's `data_len`.
+  pub fn read_file(
+    &mut self,
+    handle: FileHandle,
+    buf: &mut [u8],
+  ) -> Result<usize, FileSystemError> {
+    let open = self
+      .open_files
+      .get(handle.0)
+      .copied()
+      .flatten()
+      .ok_or(FileSystemError::InvalidHandle)?;
+
+    let header = self.read_header_at(open.header_addr);
+
+    let remaining = header.data_len.saturating_sub(open.cursor);
+    let len = remaining.min(buf.len() as u16) as usize;
+
+    // Walk the chain and slice out the requested window.
+    let data = self.read_chain(header.first_block);
+    let start = open.cursor as usize;
+    buf[..len].copy_from_slice(&data[start..start + len]);
+
+    if let Some(Some(slot)) = self.open_files.get_mut(handle.0) {
+      slot.cursor += len as u16;
     }
-    fs_header.free_addr = data_addr as u16 + content.len() as u16;
 
-    self.memcache.seek(SeekFrom::Start(0)).unwrap();
-    fs_header.write(&mut self.memcache).unwrap();
+    Ok(len)
+  }
+
+  /// Appends `buf` to the end of an open file, growing its block chain and
+  /// extending its `data_len`. Requires a writable mode.
+  pub fn write_file(
+    &mut self,
+    handle: FileHandle,
+    buf: &[u8],
+  ) -> Result<usize, FileSystemError> {
+    let open = self
+      .open_files
+      .get(handle.0)
+      .copied()
+      .flatten()
+      .ok_or(FileSystemError::InvalidHandle)?;
+
+    if open.mode == Mode::ReadOnly {
+      return Err(FileSystemError::WrongMode);
+    }
+
+    let mut header = self.read_header_at(open.header_addr);
+
+    // Rebuild the chain from the existing contents plus the appended bytes.
+    let mut data = self.read_chain(header.first_block);
+    data.truncate(header.data_len as usize);
+    data.extend_from_slice(buf);
+
+    if header.first_block != Self::NO_BLOCK {
+      self.free_chain(header.first_block);
+    }
+    header.first_block = self.write_chain(&data)?;
+    header.data_len = data.len() as u16;
+
+    self.memcache.set_position(open.header_addr as u64);
+    header.write(&mut self.memcache).unwrap();
+
+    if let Some(Some(slot)) = self.open_files.get_mut(handle.0) {
+      slot.cursor = header.data_len;
+    }
 
     self.flush();
-    Ok(file_header)
+
+    Ok(buf.len())
+  }
+
+  /// Closes an open file, freeing its slot in the handle table.
+  pub fn close_file(
+    &mut self,
+    handle: FileHandle,
+  ) -> Result<(), FileSystemError> {
+    match self.open_files.get_mut(handle.0) {
+      Some(slot @ Some(_)) => {
+        *slot = None;
+        Ok(())
+      }
+      _ => Err(FileSystemError::InvalidHandle),
+    }
   }
 
-  /// Read a file from the filesystem
-  pub fn read_file() {
-    todo!();
+  /// Reads a file header directly from its address in the table.
+  fn read_header_at(&mut self, addr: usize) -> FileHeader {
+    self.memcache.set_position(addr as u64);
+    FileHeader::read(&mut self.memcache).unwrap()
   }
 }
 
@@ -358,7 +1302,7 @@ fn stream_len(cursor: &mut Cursor<Vec<u8>>) -> io::Result<u64> {
 
 #[cfg(test)]
 mod tests {
-  use crate::{stream_len, FileSystemError, Filesystem};
+  use crate::{stream_len, FileSystemError, Filesystem, Mode};
 
   #[test]
   fn test_create_file() {
@@ -372,10 +1316,11 @@ mod tests {
       .create_file(title.to_string(), content.to_string())
       .unwrap();
 
-    // The filesystem should contain space for all file headers, the filesystem header itself, and the data
+    // The data region now holds one block for the root directory's entry
+    // list plus one full block for the single-block file.
     assert_eq!(
       stream_len(&mut filesystem.memcache).unwrap() as usize,
-      Filesystem::TABLE_SIZE + content.len()
+      Filesystem::DATA_OFFSET + 2 * Filesystem::BLOCK_SIZE
     );
   }
 
@@ -397,10 +1342,11 @@ mod tests {
       .create_file(title2.to_string(), content2.to_string())
       .unwrap();
 
-    // The filesystem should contain space for all file headers, the filesystem header itself, and the data
+    // The root directory's entry list plus each single-block file occupies
+    // one block in the data region.
     assert_eq!(
       stream_len(&mut filesystem.memcache).unwrap() as usize,
-      Filesystem::TABLE_SIZE + content.len() + content2.len()
+      Filesystem::DATA_OFFSET + 3 * Filesystem::BLOCK_SIZE
     );
 
     // The first header should contain the first data
@@ -421,8 +1367,9 @@ mod tests {
 
     filesystem.load();
 
-    // Create the maximum number of files
-    for i in 0..Filesystem::TOTAL_HEADERS {
+    // Create the maximum number of files; one slot is reserved for the root
+    // directory, so only `TOTAL_HEADERS - 1` files actually fit.
+    for i in 0..Filesystem::TOTAL_HEADERS - 1 {
       filesystem
         .create_file(format!("{title}{i}"), content.to_string())
         .unwrap();
@@ -460,25 +1407,260 @@ mod tests {
     let content2 = "This is another test.";
 
     filesystem.load();
-    let header = filesystem
+    filesystem
       .create_file(title.to_string(), content.to_string())
       .unwrap();
-    let header2 = filesystem
+    filesystem
       .create_file(title.to_string(), content2.to_string())
       .unwrap();
 
-    // The filesystem should contain the new and old data
+    // Overwriting frees the old chain, so the file still only holds one
+    // block; the root directory's entry list holds the other.
     assert_eq!(
       stream_len(&mut filesystem.memcache).unwrap() as usize,
-      Filesystem::TABLE_SIZE + content.len() + content2.len()
+      Filesystem::DATA_OFFSET + 2 * Filesystem::BLOCK_SIZE
     );
 
-    // The first header should contain the first data
+    // The file should contain the new data
+    let header = filesystem.get_file_header(title.to_string()).unwrap().unwrap();
     let data = filesystem.get_file_data(header).unwrap();
-    assert_eq!(data, content);
+    assert_eq!(data, content2);
+  }
 
-    // The second header should contain the second data
-    let data2 = filesystem.get_file_data(header2).unwrap();
-    assert_eq!(data2, content2);
+  #[test]
+  fn test_delete_reclaims_space() {
+    let mut filesystem = Filesystem::new(None);
+
+    let content = "This is a test.";
+
+    filesystem.load();
+    filesystem
+      .create_file("a.txt".to_string(), content.to_string())
+      .unwrap();
+    let after_insert = stream_len(&mut filesystem.memcache).unwrap();
+
+    filesystem.delete_file("a.txt".to_string()).unwrap();
+
+    // The slot is now free, so a same-size file reuses the reclaimed block
+    // instead of growing the disk.
+    filesystem
+      .create_file("b.txt".to_string(), content.to_string())
+      .unwrap();
+    assert_eq!(stream_len(&mut filesystem.memcache).unwrap(), after_insert);
+
+    assert!(filesystem.get_file_header("a.txt".to_string()).unwrap().is_none());
+    let header = filesystem.get_file_header("b.txt".to_string()).unwrap().unwrap();
+    assert_eq!(filesystem.get_file_data(header).unwrap(), content);
+  }
+
+  #[test]
+  fn test_create_file_in_nested_dir() {
+    let mut filesystem = Filesystem::new(None);
+
+    let content = "This is a test.";
+
+    filesystem.load();
+
+    // The intermediate directories don't exist yet, so they should be
+    // auto-created along the way.
+    let header = filesystem
+      .create_file("docs/notes/todo.txt".to_string(), content.to_string())
+      .unwrap();
+
+    assert_eq!(filesystem.get_file_data(header).unwrap(), content);
+
+    let found = filesystem
+      .get_file_header("docs/notes/todo.txt".to_string())
+      .unwrap()
+      .unwrap();
+    assert_eq!(found.name, "todo.txt");
+  }
+
+  #[test]
+  fn test_mkdir_and_list_dir() {
+    let mut filesystem = Filesystem::new(None);
+
+    filesystem.load();
+    filesystem.mkdir("docs".to_string()).unwrap();
+    filesystem
+      .create_file("docs/a.txt".to_string(), "a".to_string())
+      .unwrap();
+    filesystem
+      .create_file("docs/b.txt".to_string(), "b".to_string())
+      .unwrap();
+
+    let mut names: Vec<String> = filesystem
+      .list_dir("docs".to_string())
+      .unwrap()
+      .into_iter()
+      .map(|h| h.name)
+      .collect();
+    names.sort();
+
+    assert_eq!(names, vec!["a.txt".to_string(), "b.txt".to_string()]);
+  }
+
+  #[test]
+  fn test_delete_file_missing_parent_errors() {
+    let mut filesystem = Filesystem::new(None);
+
+    filesystem.load();
+
+    // Deletion never auto-creates directories, so a non-existent parent
+    // can't resolve.
+    let result = filesystem.delete_file("docs/a.txt".to_string());
+    assert!(matches!(result, Err(FileSystemError::FileNotFound)));
+  }
+
+  #[test]
+  fn test_create_file_bytes_stores_non_utf8_content() {
+    let mut filesystem = Filesystem::new(None);
+
+    // Invalid UTF-8 that `create_file`/`get_file_data` couldn't round-trip.
+    let content: &[u8] = &[0xff, 0x00, 0xfe, 0x01];
+
+    filesystem.load();
+    let header = filesystem
+      .create_file_bytes("bin.dat".to_string(), content)
+      .unwrap();
+
+    assert_eq!(filesystem.get_file_data_bytes(header), content);
+  }
+
+  #[test]
+  fn test_stream_write_and_read_round_trip() {
+    let mut filesystem = Filesystem::new(None);
+
+    // Large enough to span several blocks.
+    let content = "x".repeat(Filesystem::BLOCK_SIZE * 3 + 5);
+
+    filesystem.load();
+    filesystem
+      .write_file_stream("big.txt".to_string(), &mut content.as_bytes())
+      .unwrap();
+
+    let mut out = vec![];
+    filesystem
+      .read_file_stream("big.txt".to_string(), &mut out)
+      .unwrap();
+
+    assert_eq!(out, content.as_bytes());
+  }
+
+  #[test]
+  fn test_snapshot_preserves_overwritten_content() {
+    let mut filesystem = Filesystem::new(None);
+
+    filesystem.load();
+    filesystem
+      .create_file("a.txt".to_string(), "before".to_string())
+      .unwrap();
+
+    let id = filesystem.snapshot().unwrap();
+
+    // Overwriting after the snapshot must not disturb the snapshot's view.
+    filesystem
+      .create_file("a.txt".to_string(), "after".to_string())
+      .unwrap();
+
+    let live = filesystem.get_file_header("a.txt".to_string()).unwrap().unwrap();
+    assert_eq!(filesystem.get_file_data(live).unwrap(), "after");
+
+    let snapshotted = filesystem.read_snapshot_header(id, 1);
+    assert_eq!(filesystem.get_file_data(snapshotted).unwrap(), "before");
+  }
+
+  #[test]
+  fn test_delete_file_keeps_block_alive_for_snapshot() {
+    let mut filesystem = Filesystem::new(None);
+
+    filesystem.load();
+    let header = filesystem
+      .create_file("a.txt".to_string(), "content".to_string())
+      .unwrap();
+    let block = header.first_block;
+
+    let id = filesystem.snapshot().unwrap();
+    assert_eq!(filesystem.read_refcount(block), 2);
+
+    filesystem.delete_file("a.txt".to_string()).unwrap();
+
+    // The snapshot still shares the block, so it must not be reclaimed.
+    assert_eq!(filesystem.read_refcount(block), 1);
+    assert_eq!(filesystem.read_fat_entry(block), Filesystem::FAT_EOC);
+
+    filesystem.delete_snapshot(id).unwrap();
+    assert_eq!(filesystem.read_refcount(block), 0);
+    assert_eq!(filesystem.read_fat_entry(block), Filesystem::FAT_FREE);
+  }
+
+  #[test]
+  fn test_restore_snapshot_undoes_later_writes() {
+    let mut filesystem = Filesystem::new(None);
+
+    filesystem.load();
+    filesystem
+      .create_file("a.txt".to_string(), "before".to_string())
+      .unwrap();
+
+    let id = filesystem.snapshot().unwrap();
+    filesystem
+      .create_file("a.txt".to_string(), "after".to_string())
+      .unwrap();
+
+    filesystem.restore_snapshot(id).unwrap();
+
+    let header = filesystem.get_file_header("a.txt".to_string()).unwrap().unwrap();
+    assert_eq!(filesystem.get_file_data(header).unwrap(), "before");
+  }
+
+  #[test]
+  fn test_delete_snapshot_not_found() {
+    let mut filesystem = Filesystem::new(None);
+
+    filesystem.load();
+    let result = filesystem.delete_snapshot(0);
+
+    assert!(matches!(result, Err(FileSystemError::SnapshotNotFound)));
+  }
+
+  #[test]
+  fn test_write_new_truncates_existing_content() {
+    let mut filesystem = Filesystem::new(None);
+
+    filesystem.load();
+    filesystem
+      .create_file("hello.txt".to_string(), "hello".to_string())
+      .unwrap();
+
+    let handle = filesystem
+      .open_file("hello.txt".to_string(), Mode::WriteNew)
+      .unwrap();
+    filesystem.write_file(handle, b"X").unwrap();
+    filesystem.close_file(handle).unwrap();
+
+    let header =
+      filesystem.get_file_header("hello.txt".to_string()).unwrap().unwrap();
+    assert_eq!(filesystem.get_file_data(header).unwrap(), "X");
+  }
+
+  #[test]
+  fn test_append_keeps_existing_content() {
+    let mut filesystem = Filesystem::new(None);
+
+    filesystem.load();
+    filesystem
+      .create_file("hello.txt".to_string(), "hello".to_string())
+      .unwrap();
+
+    let handle = filesystem
+      .open_file("hello.txt".to_string(), Mode::Append)
+      .unwrap();
+    filesystem.write_file(handle, b"X").unwrap();
+    filesystem.close_file(handle).unwrap();
+
+    let header =
+      filesystem.get_file_header("hello.txt".to_string()).unwrap().unwrap();
+    assert_eq!(filesystem.get_file_data(header).unwrap(), "helloX");
   }
 }
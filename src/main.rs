@@ -2,7 +2,7 @@ use std::fs::OpenOptions;
 
 use rfs::filesystem::{
   BlockAlign, BlockKindData, BlockKindHeader, BlockKindMain, BlockKindTitle,
-  File, Filesystem,
+  Filesystem, FilesystemOptions,
 };
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -13,6 +13,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
       .create(true)
       .truncate(true)
       .open("harddrive.bin")?,
+    FilesystemOptions::default(),
   );
 
   filesystem.init(
@@ -22,14 +23,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
       + BlockKindData::super_block_size(),
   )?;
 
-  filesystem.create(File::new(
-    "hello.txt".to_owned(),
-    "Hello, World!".to_owned(),
-  ))?;
-  filesystem.create(File::new(
-    "hello2.txt".to_owned(),
-    "Hello, from the filesystem!".to_owned(),
-  ))?;
+  filesystem.insert("hello.txt".to_owned(), "Hello, World!")?;
+  filesystem.insert("hello2.txt".to_owned(), "Hello, from the filesystem!")?;
 
   println!("{:?}", filesystem.list());
 